@@ -0,0 +1,362 @@
+use crate::merkle::{blob_leaf, ProofVerificationError};
+use crate::vectorx::range_hash;
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::{AvailBridgeContract, AvailHeadData, BridgeApiMerkleProof, Result};
+use alloy::primitives::Address;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_transport::Transport;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use std::fmt;
+
+/// Calls `verifyBlobLeaf` as a view function, so data can be attested without sending a
+/// transaction.
+pub async fn verify_blob_leaf<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    proof: BridgeApiMerkleProof,
+) -> Result<bool>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(bridge.verifyBlobLeaf(proof.into()).call().await?._0)
+}
+
+/// Calls `verifyBridgeLeaf` as a view function, checking that a message is committed without
+/// claiming it.
+pub async fn verify_bridge_leaf<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    proof: BridgeApiMerkleProof,
+) -> Result<bool>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    Ok(bridge.verifyBridgeLeaf(proof.into()).call().await?._0)
+}
+
+/// Self-contained record that a `submit_data` blob was included on Avail and committed to
+/// Ethereum by VectorX. It can be handed to a third party as JSON and verified offline, or
+/// against an Ethereum RPC with `verify_on_chain`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationCertificate {
+    /// `keccak256` of the submitted data.
+    pub data_hash: H256,
+    pub block_hash: H256,
+    pub block_number: u64,
+    pub extrinsic_index: u32,
+    pub proof: BridgeApiMerkleProof,
+    pub bridge_address: Address,
+    pub vectorx_address: Address,
+    /// Avail block range of the VectorX commitment containing the block.
+    pub range: AvailHeadData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CertificateError {
+    DataHashMismatch {
+        expected: H256,
+        leaf: H256,
+    },
+    BlockHashMismatch {
+        expected: H256,
+        proof: H256,
+    },
+    BlockNotInRange {
+        block_number: u64,
+        start: u64,
+        end: u64,
+    },
+    /// The proof is for a different VectorX commitment than the certified range.
+    RangeHashMismatch {
+        expected: H256,
+        proof: H256,
+    },
+    /// The proof is for a different block of the range than the certified block.
+    DataRootIndexMismatch {
+        expected: u32,
+        proof: u32,
+    },
+    InvalidProof(ProofVerificationError),
+    /// The certificate names a different bridge contract than the one the verifier trusts.
+    BridgeMismatch {
+        expected: Address,
+        certificate: Address,
+    },
+    VectorxMismatch {
+        expected: Address,
+        bridge: Address,
+    },
+    RejectedByBridge,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertificateError::DataHashMismatch { expected, leaf } => {
+                write!(
+                    f,
+                    "Data hash {expected:?} does not match proof leaf {leaf:?}"
+                )
+            }
+            CertificateError::BlockHashMismatch { expected, proof } => {
+                write!(
+                    f,
+                    "Block hash {expected:?} does not match proof block {proof:?}"
+                )
+            }
+            CertificateError::BlockNotInRange {
+                block_number,
+                start,
+                end,
+            } => write!(f, "Block {block_number} is not in range {start}..={end}"),
+            CertificateError::RangeHashMismatch { expected, proof } => {
+                write!(
+                    f,
+                    "Range hash {expected:?} does not match proof range hash {proof:?}"
+                )
+            }
+            CertificateError::DataRootIndexMismatch { expected, proof } => {
+                write!(
+                    f,
+                    "Data root index {expected} does not match proof index {proof}"
+                )
+            }
+            CertificateError::InvalidProof(e) => write!(f, "{e}"),
+            CertificateError::BridgeMismatch {
+                expected,
+                certificate,
+            } => write!(
+                f,
+                "Certificate names bridge {certificate}, expected {expected}"
+            ),
+            CertificateError::VectorxMismatch { expected, bridge } => {
+                write!(
+                    f,
+                    "Bridge uses VectorX {bridge}, certificate names {expected}"
+                )
+            }
+            CertificateError::RejectedByBridge => {
+                write!(f, "Bridge contract rejected the proof")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CertificateError {}
+
+impl From<ProofVerificationError> for CertificateError {
+    fn from(value: ProofVerificationError) -> Self {
+        CertificateError::InvalidProof(value)
+    }
+}
+
+impl AttestationCertificate {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Checks the certificate without any network access: the proof's leaf must be the data
+    /// hash, the proof must be for the certified block, the block must be in the range, the
+    /// proof must be for that range and block, and it must hash up to the data root commitment.
+    /// This only proves the certificate is self-consistent; whoever wrote it also chose the
+    /// commitment, so use `verify_on_chain` to check that VectorX actually committed it.
+    pub fn verify(&self) -> Result<(), CertificateError> {
+        if self.proof.leaf != self.data_hash {
+            return Err(CertificateError::DataHashMismatch {
+                expected: self.data_hash,
+                leaf: self.proof.leaf,
+            });
+        }
+        if self.proof.block_hash != self.block_hash {
+            return Err(CertificateError::BlockHashMismatch {
+                expected: self.block_hash,
+                proof: self.proof.block_hash,
+            });
+        }
+        if !self.range.contains(self.block_number) {
+            return Err(CertificateError::BlockNotInRange {
+                block_number: self.block_number,
+                start: self.range.start,
+                end: self.range.end,
+            });
+        }
+        // The range starts after the last block of the previous commitment, which is the start
+        // the commitment is keyed by.
+        let range_hash = range_hash(
+            self.range.start.saturating_sub(1) as u32,
+            self.range.end as u32,
+        );
+        if self.proof.range_hash != range_hash {
+            return Err(CertificateError::RangeHashMismatch {
+                expected: range_hash,
+                proof: self.proof.range_hash,
+            });
+        }
+        let data_root_index = (self.block_number - self.range.start) as u32;
+        if self.proof.data_root_index != data_root_index {
+            return Err(CertificateError::DataRootIndexMismatch {
+                expected: data_root_index,
+                proof: self.proof.data_root_index,
+            });
+        }
+        self.proof.verify_blob_leaf()?;
+        Ok(())
+    }
+
+    /// Returns true if `data` is the data the certificate was issued for.
+    pub fn is_for_data(&self, data: &[u8]) -> bool {
+        blob_leaf(data) == self.data_hash
+    }
+
+    /// Verifies the certificate offline, then checks it against `bridge`, the bridge contract
+    /// the verifier trusts: the certificate must name it, it must still use the certified VectorX
+    /// contract and it must accept the proof with a `verifyBlobLeaf` view call. The bridge
+    /// address in the certificate is never trusted on its own, since a forged certificate could
+    /// name a contract that accepts anything.
+    pub async fn verify_on_chain(&self, ethereum_url: Url, bridge: Address) -> Result<()> {
+        self.verify()?;
+        if self.bridge_address != bridge {
+            return Err(CertificateError::BridgeMismatch {
+                expected: bridge,
+                certificate: self.bridge_address,
+            }
+            .into());
+        }
+        let provider = ProviderBuilder::new().on_http(ethereum_url);
+        let contract = AvailBridgeContract::new(bridge, &provider);
+        let vectorx = contract.vectorx().call().await?._0;
+        if vectorx != self.vectorx_address {
+            return Err(CertificateError::VectorxMismatch {
+                expected: self.vectorx_address,
+                bridge: vectorx,
+            }
+            .into());
+        }
+        if !verify_blob_leaf(&contract, self.proof.clone()).await? {
+            return Err(CertificateError::RejectedByBridge.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BridgeError;
+
+    fn certificate() -> AttestationCertificate {
+        let proof: BridgeApiMerkleProof =
+            serde_json::from_str(include_str!("../tests/fixtures/blob_proof.json")).unwrap();
+        AttestationCertificate {
+            data_hash: proof.leaf,
+            block_hash: proof.block_hash,
+            block_number: 1006,
+            extrinsic_index: 1,
+            proof,
+            bridge_address: Address::ZERO,
+            vectorx_address: Address::ZERO,
+            range: AvailHeadData {
+                start: 1001,
+                end: 1016,
+            },
+        }
+    }
+
+    #[test]
+    fn certificate_verifies() {
+        let certificate = certificate();
+        assert_eq!(certificate.verify(), Ok(()));
+        assert!(certificate.is_for_data(b"Hello, Avail bridge!"));
+        assert!(!certificate.is_for_data(b"Hello, Avail bridge?"));
+    }
+
+    #[test]
+    fn tampered_data_hash_is_rejected() {
+        let mut certificate = certificate();
+        certificate.data_hash.0[0] ^= 1;
+        assert!(matches!(
+            certificate.verify(),
+            Err(CertificateError::DataHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_block_hash_is_rejected() {
+        let mut certificate = certificate();
+        certificate.block_hash.0[0] ^= 1;
+        assert!(matches!(
+            certificate.verify(),
+            Err(CertificateError::BlockHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn block_outside_range_is_rejected() {
+        let mut certificate = certificate();
+        certificate.block_number = 1017;
+        assert_eq!(
+            certificate.verify(),
+            Err(CertificateError::BlockNotInRange {
+                block_number: 1017,
+                start: 1001,
+                end: 1016,
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_range_is_rejected() {
+        let mut certificate = certificate();
+        certificate.range.end = 1020;
+        assert!(matches!(
+            certificate.verify(),
+            Err(CertificateError::RangeHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_block_number_is_rejected() {
+        let mut certificate = certificate();
+        certificate.block_number = 1007;
+        assert_eq!(
+            certificate.verify(),
+            Err(CertificateError::DataRootIndexMismatch {
+                expected: 6,
+                proof: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_proof_is_rejected() {
+        let mut certificate = certificate();
+        certificate.proof.data_root_proof[0].0[0] ^= 1;
+        assert!(matches!(
+            certificate.verify(),
+            Err(CertificateError::InvalidProof(
+                ProofVerificationError::InvalidDataRootProof { .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn certificate_for_other_bridge_is_rejected_before_calling_it() {
+        // Nothing listens on the URL: the bridge address must be checked first.
+        let url = "http://127.0.0.1:9".parse().unwrap();
+        let trusted = Address::repeat_byte(1);
+        assert!(matches!(
+            certificate().verify_on_chain(url, trusted).await,
+            Err(BridgeError::InvalidCertificate(CertificateError::BridgeMismatch {
+                expected,
+                certificate,
+            })) if expected == trusted && certificate == Address::ZERO
+        ));
+    }
+}
@@ -0,0 +1,810 @@
+use crate::attestation::{verify_blob_leaf, verify_bridge_leaf, AttestationCertificate};
+use crate::data_proof::build_eth_proof;
+use crate::head_watcher::{HeadNotifications, HeadWatcher};
+use crate::merkle::{blob_leaf, message_leaf};
+use crate::metrics;
+use crate::simulate::{send_simulated, Preflight};
+use crate::status::{claim_status, ClaimStatus};
+use crate::tokens::{AVAIL_ASSET_ID, ETH_ASSET_ID};
+use crate::vectorx::{CommittedRange, HeadTracker};
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::{
+    connect_avail, AvailBridgeContract, AvailHeadData, BridgeApiClient, BridgeApiMerkleProof,
+    BridgeError, Config, ProofSource, Result, AVAIL_DOMAIN, DEFAULT_POLL_INTERVAL, ETHEREUM_DOMAIN,
+};
+use alloy_network::EthereumWallet;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_transport::Transport;
+use avail_rust::avail::vector::calls::types::send_message::Message;
+use avail_rust::avail::vector::events::MessageSubmitted;
+use avail_rust::avail_core::data_proof::{
+    AddressedMessage as CoreAddressedMessage, Message as CoreMessage,
+};
+use avail_rust::subxt::tx::TxProgress;
+use avail_rust::subxt::OnlineClient;
+use avail_rust::{avail, AvailConfig, AvailExtrinsicParamsBuilder, Data, Keypair, SDK};
+use serde::{Deserialize, Serialize};
+use sp_core::{blake2_256, H256};
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::{info, info_span, instrument, warn, Span};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailToEthStage {
+    Submitted,
+    Finalized,
+    Committed,
+    ProofFetched,
+    /// The proof was checked against the bridge with a view call, without a transaction.
+    Verified,
+    Claimed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AvailToEthKind {
+    Message,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IncludedTx {
+    pub block_hash: H256,
+    pub extrinsic_index: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FinalizedTx {
+    pub block_hash: H256,
+    pub block_number: u64,
+    pub extrinsic_index: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum ClaimOutcome {
+    Claimed(TransactionReceipt),
+    AlreadyBridged,
+}
+
+#[derive(Debug, Clone)]
+enum Expected {
+    /// The submitted message. Its id is assigned on Avail and filled in once it is finalized.
+    Message(CoreAddressedMessage),
+    Data {
+        leaf: H256,
+    },
+}
+
+/// Handle to an Avail -> Ethereum transfer. Each stage method drives the transfer up to that
+/// stage and caches the result, so stages can be awaited one by one or all at once via `claim`.
+pub struct AvailToEthTransfer {
+    config: Config,
+    sdk: Option<SDK>,
+    bridge_api: BridgeApiClient,
+    eth_signer: PrivateKeySigner,
+    kind: AvailToEthKind,
+    expected: Option<Expected>,
+    /// Id of the message to prove when the extrinsic sends several, known to the relayer.
+    expected_message_id: Option<u64>,
+    poll_interval: Duration,
+    tx_hash: Option<H256>,
+    /// Latest finalized Avail block when the extrinsic was submitted.
+    submitted_at: Option<u32>,
+    progress: Option<TxProgress<AvailConfig, OnlineClient<AvailConfig>>>,
+    included: Option<IncludedTx>,
+    finalized: Option<FinalizedTx>,
+    committed: Option<AvailHeadData>,
+    proof: Option<BridgeApiMerkleProof>,
+    verified: Option<bool>,
+    claimed: Option<ClaimOutcome>,
+    span: Span,
+}
+
+/// Sends `message` to `recipient` on Ethereum through the `vector` pallet.
+pub async fn bridge_avail_to_eth(
+    config: &Config,
+    avail_signer: &Keypair,
+    eth_signer: PrivateKeySigner,
+    message: Message,
+    recipient: H256,
+) -> Result<AvailToEthTransfer> {
+    let sdk = connect_avail(&config.avail_rpc_url).await?;
+    let expected = Expected::Message(CoreAddressedMessage {
+        message: core_message(&message)?,
+        from: H256(avail_signer.public_key().0),
+        to: recipient,
+        origin_domain: AVAIL_DOMAIN,
+        destination_domain: ETHEREUM_DOMAIN,
+        id: 0,
+    });
+    let call = avail::tx()
+        .vector()
+        .send_message(message, recipient, ETHEREUM_DOMAIN);
+    let submitted_at = sdk.api.blocks().at_latest().await?.number();
+    let params = AvailExtrinsicParamsBuilder::new().build();
+    let progress = sdk
+        .api
+        .tx()
+        .sign_and_submit_then_watch(&call, avail_signer, params)
+        .await?;
+    AvailToEthTransfer::new(
+        config,
+        sdk,
+        eth_signer,
+        AvailToEthKind::Message,
+        expected,
+        submitted_at,
+        progress,
+    )
+}
+
+/// Submits `data` to Avail so it can be attested on Ethereum with `verifyBlobLeaf`.
+pub async fn submit_data_for_attestation(
+    config: &Config,
+    avail_signer: &Keypair,
+    eth_signer: PrivateKeySigner,
+    data: Vec<u8>,
+) -> Result<AvailToEthTransfer> {
+    let sdk = connect_avail(&config.avail_rpc_url).await?;
+    let expected = Expected::Data {
+        leaf: blob_leaf(&data),
+    };
+    let call = avail::tx().data_availability().submit_data(Data(data));
+    let submitted_at = sdk.api.blocks().at_latest().await?.number();
+    let params = AvailExtrinsicParamsBuilder::new().build();
+    let progress = sdk
+        .api
+        .tx()
+        .sign_and_submit_then_watch(&call, avail_signer, params)
+        .await?;
+    AvailToEthTransfer::new(
+        config,
+        sdk,
+        eth_signer,
+        AvailToEthKind::Data,
+        expected,
+        submitted_at,
+        progress,
+    )
+}
+
+/// Finalized blocks after its submission searched for an extrinsic by `find_inclusion`.
+/// Extrinsics are mortal, so one that is not included by then never will be.
+const MAX_INCLUSION_SEARCH: u32 = 256;
+
+/// Finds the finalized block including the extrinsic `tx_hash`, submitted when `submitted_at` was
+/// the latest finalized block. Used for transfers journaled before their inclusion was known.
+pub async fn find_inclusion(sdk: &SDK, tx_hash: H256, submitted_at: u32) -> Result<IncludedTx> {
+    let finalized = sdk.api.blocks().at_latest().await?.number();
+    let last = finalized.min(submitted_at + MAX_INCLUSION_SEARCH);
+    for block_number in submitted_at + 1..=last {
+        let block_hash = sdk.rpc.chain.get_block_hash(Some(block_number)).await?;
+        let extrinsics = sdk.api.blocks().at(block_hash).await?.extrinsics().await?;
+        for extrinsic in extrinsics.iter() {
+            let extrinsic = extrinsic?;
+            if H256(blake2_256(extrinsic.bytes())) == tx_hash {
+                return Ok(IncludedTx {
+                    block_hash,
+                    extrinsic_index: extrinsic.index(),
+                });
+            }
+        }
+    }
+    if last < submitted_at + MAX_INCLUSION_SEARCH {
+        return Err(BridgeError::Transfer(format!(
+            "Transaction {tx_hash:?} is not in a finalized block yet"
+        )));
+    }
+    Err(BridgeError::Transfer(format!(
+        "Transaction {tx_hash:?} was not included within {MAX_INCLUSION_SEARCH} blocks"
+    )))
+}
+
+/// Converts a `send_message` call's message to the form the bridge leaf is computed from.
+fn core_message(message: &Message) -> Result<CoreMessage> {
+    Ok(match message {
+        Message::ArbitraryMessage(data) => CoreMessage::ArbitraryMessage(
+            data.0
+                .clone()
+                .try_into()
+                .map_err(|_| BridgeError::Decode("Message data too long".into()))?,
+        ),
+        Message::FungibleToken { asset_id, amount } => CoreMessage::FungibleToken {
+            asset_id: *asset_id,
+            amount: *amount,
+        },
+    })
+}
+
+impl AvailToEthTransfer {
+    fn new(
+        config: &Config,
+        sdk: SDK,
+        eth_signer: PrivateKeySigner,
+        kind: AvailToEthKind,
+        expected: Expected,
+        submitted_at: u32,
+        progress: TxProgress<AvailConfig, OnlineClient<AvailConfig>>,
+    ) -> Result<Self> {
+        let tx_hash = progress.extrinsic_hash();
+        let span = transfer_span(kind);
+        span.record("avail_tx_hash", tracing::field::debug(tx_hash));
+        span.in_scope(|| info!("Submitted to Avail"));
+        metrics::avail_to_eth_stage(AvailToEthStage::Submitted);
+        Ok(Self {
+            config: config.clone(),
+            sdk: Some(sdk),
+            bridge_api: BridgeApiClient::new(config.bridge_api_url.as_str())?,
+            eth_signer,
+            kind,
+            expected: Some(expected),
+            expected_message_id: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            tx_hash: Some(tx_hash),
+            submitted_at: Some(submitted_at),
+            progress: Some(progress),
+            included: None,
+            finalized: None,
+            committed: None,
+            proof: None,
+            verified: None,
+            claimed: None,
+            span,
+        })
+    }
+
+    /// Picks up a transfer whose extrinsic was already included at `block_hash`, e.g. after a
+    /// restart. The block does not need to be finalized yet. The proof is still verified, but
+    /// not matched against the original submission.
+    pub fn resume(
+        config: &Config,
+        sdk: SDK,
+        eth_signer: PrivateKeySigner,
+        kind: AvailToEthKind,
+        block_hash: H256,
+        extrinsic_index: u32,
+    ) -> Result<Self> {
+        let span = transfer_span(kind);
+        span.record("avail_block_hash", tracing::field::debug(block_hash));
+        span.record("extrinsic_index", extrinsic_index);
+        Ok(Self {
+            config: config.clone(),
+            sdk: Some(sdk),
+            bridge_api: BridgeApiClient::new(config.bridge_api_url.as_str())?,
+            eth_signer,
+            kind,
+            expected: None,
+            expected_message_id: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            tx_hash: None,
+            submitted_at: None,
+            progress: None,
+            included: Some(IncludedTx {
+                block_hash,
+                extrinsic_index,
+            }),
+            finalized: None,
+            committed: None,
+            proof: None,
+            verified: None,
+            claimed: None,
+            span,
+        })
+    }
+
+    /// Only accepts a proof of the message `message_id`, e.g. for a resumed transfer whose
+    /// extrinsic sends more than one message.
+    pub fn with_message_id(mut self, message_id: u64) -> Self {
+        self.expected_message_id = Some(message_id);
+        self
+    }
+
+    /// Sets the longest wait between checks of the VectorX head.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn kind(&self) -> AvailToEthKind {
+        self.kind
+    }
+
+    /// Hash of the submitted extrinsic, unknown for resumed transfers.
+    pub fn tx_hash(&self) -> Option<H256> {
+        self.tx_hash
+    }
+
+    /// Latest finalized Avail block when the extrinsic was submitted, the block `find_inclusion`
+    /// searches from.
+    pub fn submitted_at(&self) -> Option<u32> {
+        self.submitted_at
+    }
+
+    pub fn included_tx(&self) -> Option<IncludedTx> {
+        self.included
+    }
+
+    pub fn finalized_tx(&self) -> Option<FinalizedTx> {
+        self.finalized
+    }
+
+    /// Id of the bridged message, known once the proof is fetched.
+    pub fn message_id(&self) -> Option<u64> {
+        self.proof
+            .as_ref()
+            .and_then(|proof| proof.message.as_ref())
+            .map(|message| message.id)
+    }
+
+    /// Span of the transfer, carrying its hashes and message id once they are known. Each stage
+    /// runs in a child span named after it.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn claim_outcome(&self) -> Option<&ClaimOutcome> {
+        self.claimed.as_ref()
+    }
+
+    pub fn stage(&self) -> AvailToEthStage {
+        if self.claimed.is_some() {
+            AvailToEthStage::Claimed
+        } else if self.verified.is_some() {
+            AvailToEthStage::Verified
+        } else if self.proof.is_some() {
+            AvailToEthStage::ProofFetched
+        } else if self.committed.is_some() {
+            AvailToEthStage::Committed
+        } else if self.finalized.is_some() {
+            AvailToEthStage::Finalized
+        } else {
+            AvailToEthStage::Submitted
+        }
+    }
+
+    /// Waits for the extrinsic to be included in a block, which may not be finalized yet. The
+    /// block is known from here on, so a journaled transfer can be resumed.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn included(&mut self) -> Result<IncludedTx> {
+        if let Some(included) = self.included {
+            return Ok(included);
+        }
+        let progress = self.progress.take().ok_or(BridgeError::Transfer(
+            "Transaction progress already consumed".into(),
+        ))?;
+        let tx_in_block = progress
+            .wait_for_in_block()
+            .await
+            .map_err(|e| BridgeError::Avail(format!("Transaction failed: {e}")))?;
+        let events = tx_in_block.wait_for_success().await?;
+        if let Some(Expected::Message(message)) = &mut self.expected {
+            let submitted = events
+                .find_first::<MessageSubmitted>()?
+                .ok_or(BridgeError::Avail("No MessageSubmitted event".into()))?;
+            message.id = submitted.message_id;
+        }
+        let included = IncludedTx {
+            block_hash: tx_in_block.block_hash(),
+            extrinsic_index: events.extrinsic_index(),
+        };
+        self.span
+            .record(
+                "avail_block_hash",
+                tracing::field::debug(included.block_hash),
+            )
+            .record("extrinsic_index", included.extrinsic_index);
+        info!("Included on Avail");
+        self.included = Some(included);
+        Ok(included)
+    }
+
+    /// Waits for the block including the extrinsic to be finalized on Avail. Fails if another
+    /// block is finalized at its height.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn finalized(&mut self) -> Result<FinalizedTx> {
+        if let Some(finalized) = self.finalized {
+            return Ok(finalized);
+        }
+        let included = self.included().await?;
+        let sdk = self
+            .sdk
+            .as_ref()
+            .ok_or(BridgeError::Transfer("No Avail client".into()))?;
+        let block_number = sdk.api.blocks().at(included.block_hash).await?.number();
+        let mut blocks = sdk.api.blocks().subscribe_finalized().await?;
+        loop {
+            let block = blocks.next().await.ok_or(BridgeError::Avail(
+                "Finalized block subscription ended".into(),
+            ))??;
+            if block.number() >= block_number {
+                break;
+            }
+        }
+        self.set_finalized(included, block_number).await
+    }
+
+    /// Checks once whether the block including the extrinsic is finalized, without waiting.
+    /// Fails if another block is finalized at its height.
+    #[instrument(name = "finalized", parent = &self.span, skip_all)]
+    pub async fn try_finalized(&mut self) -> Result<Option<FinalizedTx>> {
+        if let Some(finalized) = self.finalized {
+            return Ok(Some(finalized));
+        }
+        let included = self.included().await?;
+        let sdk = self
+            .sdk
+            .as_ref()
+            .ok_or(BridgeError::Transfer("No Avail client".into()))?;
+        let block_number = sdk.api.blocks().at(included.block_hash).await?.number();
+        if sdk.api.blocks().at_latest().await?.number() < block_number {
+            return Ok(None);
+        }
+        self.set_finalized(included, block_number).await.map(Some)
+    }
+
+    /// Records the transfer as finalized once the finalized chain reached `block_number`, after
+    /// checking that its block is the one finalized at that height.
+    async fn set_finalized(
+        &mut self,
+        included: IncludedTx,
+        block_number: u32,
+    ) -> Result<FinalizedTx> {
+        let sdk = self
+            .sdk
+            .as_ref()
+            .ok_or(BridgeError::Transfer("No Avail client".into()))?;
+        let finalized_hash = sdk.rpc.chain.get_block_hash(Some(block_number)).await?;
+        if finalized_hash != included.block_hash {
+            return Err(BridgeError::Avail(format!(
+                "Block {:?} was not finalized, {finalized_hash:?} was finalized at height {block_number}",
+                included.block_hash
+            )));
+        }
+        let finalized = FinalizedTx {
+            block_hash: included.block_hash,
+            block_number: block_number as u64,
+            extrinsic_index: included.extrinsic_index,
+        };
+        info!(block_number = finalized.block_number, "Finalized on Avail");
+        self.finalized = Some(finalized);
+        metrics::avail_to_eth_stage(AvailToEthStage::Finalized);
+        Ok(finalized)
+    }
+
+    /// Waits until VectorX has committed a range on Ethereum containing the finalized block,
+    /// woken up by `HeadUpdate` logs if `ethereum_ws_url` is configured.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn committed(&mut self) -> Result<AvailHeadData> {
+        if let Some(committed) = &self.committed {
+            return Ok(committed.clone());
+        }
+        let finalized = self.finalized().await?;
+        let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+        let contract = AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+        let head_tracker = HeadTracker::for_bridge(&contract)
+            .await?
+            .with_log_scan(self.config.log_scan());
+        let notifications = match &self.config.ethereum_ws_url {
+            Some(ws_url) => HeadNotifications::vectorx_head_updates(ws_url, head_tracker.address())
+                .await
+                .inspect_err(|e| warn!(error = %e, "VectorX head subscription failed, polling"))
+                .ok(),
+            None => None,
+        };
+        info!(
+            block_number = finalized.block_number,
+            "Waiting for VectorX to commit the block"
+        );
+        let mut watcher =
+            HeadWatcher::new(head_tracker, self.poll_interval).with_notifications(notifications);
+        let range = watcher.wait_until_covers(finalized.block_number).await?;
+        Ok(self.set_committed(watcher.head(), finalized, range).await)
+    }
+
+    /// Checks once whether the finalized block is committed, without waiting.
+    #[instrument(name = "committed", parent = &self.span, skip_all)]
+    pub async fn try_committed(&mut self) -> Result<Option<AvailHeadData>> {
+        if let Some(committed) = &self.committed {
+            return Ok(Some(committed.clone()));
+        }
+        let finalized = self.finalized().await?;
+        let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+        let contract = AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+        let head_tracker = HeadTracker::for_bridge(&contract)
+            .await?
+            .with_log_scan(self.config.log_scan());
+        let Some(range) = head_tracker.committed_range(finalized.block_number).await? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            self.set_committed(&head_tracker, finalized, range).await,
+        ))
+    }
+
+    async fn set_committed<T, P>(
+        &mut self,
+        head_tracker: &HeadTracker<T, P>,
+        finalized: FinalizedTx,
+        range: CommittedRange,
+    ) -> AvailHeadData
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        if metrics::ENABLED {
+            if let Some(delay) = self
+                .commitment_delay(head_tracker, finalized.block_hash, &range)
+                .await
+            {
+                metrics::commitment_delay(delay);
+            }
+        }
+        metrics::avail_to_eth_stage(AvailToEthStage::Committed);
+        let range = AvailHeadData::from(range);
+        info!(start = range.start, end = range.end, "Committed by VectorX");
+        self.committed = Some(range.clone());
+        range
+    }
+
+    /// Time from the Avail block to the Ethereum block that committed its range, so it does not
+    /// depend on when this process sees the commitment.
+    async fn commitment_delay<T, P>(
+        &self,
+        head_tracker: &HeadTracker<T, P>,
+        block_hash: H256,
+        range: &CommittedRange,
+    ) -> Option<Duration>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let sdk = self.sdk.as_ref()?;
+        let produced_at = sdk
+            .api
+            .storage()
+            .at(block_hash)
+            .fetch(&avail::storage().timestamp().now())
+            .await
+            .ok()??;
+        let committed_at = head_tracker.commitment_timestamp(range).await.ok()?;
+        Duration::from_secs(committed_at).checked_sub(Duration::from_millis(produced_at))
+    }
+
+    /// Fetches the Merkle proof for the extrinsic once it is committed and verifies it locally.
+    /// With `ProofSource::Rpc` the proof is built from the Avail node and VectorX instead of the
+    /// Bridge API.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn proof(&mut self) -> Result<BridgeApiMerkleProof> {
+        if let Some(proof) = &self.proof {
+            return Ok(proof.clone());
+        }
+        let finalized = self.finalized().await?;
+        self.committed().await?;
+        let started = Instant::now();
+        let proof = match self.config.proof_source {
+            ProofSource::BridgeApi => {
+                self.bridge_api
+                    .eth_proof(finalized.block_hash, finalized.extrinsic_index)
+                    .await?
+            }
+            ProofSource::Rpc => {
+                if self.sdk.is_none() {
+                    self.sdk = Some(connect_avail(&self.config.avail_rpc_url).await?);
+                }
+                let sdk = self
+                    .sdk
+                    .as_ref()
+                    .ok_or(BridgeError::Transfer("No Avail client".into()))?;
+                let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+                let contract =
+                    AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+                build_eth_proof(
+                    sdk,
+                    &contract,
+                    finalized.block_hash,
+                    finalized.extrinsic_index,
+                    self.config.log_scan(),
+                )
+                .await?
+            }
+        };
+        match &self.expected {
+            None => {}
+            Some(Expected::Message(message)) => {
+                if proof.leaf != message_leaf(message) {
+                    return Err(BridgeError::Transfer(format!(
+                        "Proof is for a different message: {:?}",
+                        proof.message
+                    )));
+                }
+            }
+            Some(Expected::Data { leaf }) => {
+                if proof.leaf != *leaf {
+                    return Err(BridgeError::Transfer(format!(
+                        "Proof is for different data, leaf: {:?}",
+                        proof.leaf
+                    )));
+                }
+            }
+        }
+        if let Some(expected_id) = self.expected_message_id {
+            let proved_id = proof.message.as_ref().map(|message| message.id);
+            if proved_id != Some(expected_id) {
+                return Err(BridgeError::Transfer(format!(
+                    "Proof is for message {proved_id:?}, expected message {expected_id}"
+                )));
+            }
+        }
+        proof.verify()?;
+        if let Some(message) = &proof.message {
+            self.span.record("message_id", message.id);
+        }
+        info!(source = ?self.config.proof_source, "Proof fetched");
+        metrics::avail_to_eth_proof_fetched(self.config.proof_source, started.elapsed());
+        metrics::avail_to_eth_stage(AvailToEthStage::ProofFetched);
+        self.proof = Some(proof.clone());
+        Ok(proof)
+    }
+
+    /// Claims the message on Ethereum, or sends `verifyBlobLeaf` as a transaction for submitted
+    /// data. `verifyBlobLeaf` is a view function, so that transaction only leaves its calldata on
+    /// Ethereum. Messages that were already bridged are not claimed again. The claim is simulated
+    /// first and only broadcast if the simulation succeeds.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn claim(&mut self) -> Result<ClaimOutcome> {
+        if let Some(claimed) = &self.claimed {
+            return Ok(claimed.clone());
+        }
+        let proof = self.proof().await?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(self.eth_signer.clone()))
+            .on_http(self.config.parse_ethereum_url()?);
+        let contract = AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+
+        if let Some(message) = &proof.message {
+            if self.kind == AvailToEthKind::Message
+                && claim_status(&contract, message).await? == ClaimStatus::AlreadyBridged
+            {
+                info!("Message was already bridged");
+                self.claimed = Some(ClaimOutcome::AlreadyBridged);
+                return Ok(ClaimOutcome::AlreadyBridged);
+            }
+        }
+        let sent = self
+            .send_claim(&contract, proof, false)
+            .await
+            .inspect_err(metrics::record_revert)?;
+        let claimed = match sent {
+            Preflight::Sent(receipt) => {
+                self.span.record(
+                    "eth_tx_hash",
+                    tracing::field::display(receipt.transaction_hash),
+                );
+                info!(gas_used = receipt.gas_used, "Claimed on Ethereum");
+                metrics::claim_gas_used(receipt.gas_used);
+                ClaimOutcome::Claimed(receipt)
+            }
+            Preflight::Simulated { .. } => unreachable!("claim is not a dry run"),
+        };
+        metrics::avail_to_eth_stage(AvailToEthStage::Claimed);
+        self.claimed = Some(claimed.clone());
+        Ok(claimed)
+    }
+
+    /// Checks the proof against the bridge with `verifyBlobLeaf` for data or `verifyBridgeLeaf`
+    /// for messages as a view call, so no transaction is sent.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn verify(&mut self) -> Result<bool> {
+        if let Some(verified) = self.verified {
+            return Ok(verified);
+        }
+        let proof = self.proof().await?;
+        let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+        let contract = AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+        let verified = match self.kind {
+            AvailToEthKind::Data => verify_blob_leaf(&contract, proof).await?,
+            AvailToEthKind::Message => verify_bridge_leaf(&contract, proof).await?,
+        };
+        info!(verified, "Proof checked against the bridge");
+        metrics::avail_to_eth_stage(AvailToEthStage::Verified);
+        self.verified = Some(verified);
+        Ok(verified)
+    }
+
+    /// Builds a portable certificate for submitted data once its proof is fetched.
+    pub async fn certificate(&mut self) -> Result<AttestationCertificate> {
+        if self.kind != AvailToEthKind::Data {
+            return Err(BridgeError::Transfer(
+                "Certificates are only issued for submitted data".into(),
+            ));
+        }
+        let finalized = self.finalized().await?;
+        let range = self.committed().await?;
+        let proof = self.proof().await?;
+        let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+        let bridge_address = self.config.parse_contract_address()?;
+        let contract = AvailBridgeContract::new(bridge_address, &provider);
+        let vectorx_address = contract.vectorx().call().await?._0;
+        Ok(AttestationCertificate {
+            data_hash: proof.leaf,
+            block_hash: finalized.block_hash,
+            block_number: finalized.block_number,
+            extrinsic_index: finalized.extrinsic_index,
+            proof,
+            bridge_address,
+            vectorx_address,
+            range,
+        })
+    }
+
+    /// Simulates the claim without broadcasting it and returns its gas estimate. A claim that
+    /// would revert returns the decoded revert, e.g. `AlreadyBridged`.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn simulate_claim(&mut self) -> Result<u128> {
+        let proof = self.proof().await?;
+        let provider = ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::from(self.eth_signer.clone()))
+            .on_http(self.config.parse_ethereum_url()?);
+        let contract = AvailBridgeContract::new(self.config.parse_contract_address()?, &provider);
+        match self.send_claim(&contract, proof, true).await? {
+            Preflight::Simulated { gas_estimate } => Ok(gas_estimate),
+            Preflight::Sent(_) => unreachable!("dry run does not broadcast"),
+        }
+    }
+
+    async fn send_claim<T, P>(
+        &self,
+        contract: &AvailBridgeContractInstance<T, P>,
+        proof: BridgeApiMerkleProof,
+        dry_run: bool,
+    ) -> Result<Preflight>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let sender = self.eth_signer.address();
+        if self.kind == AvailToEthKind::Data {
+            let call = contract.verifyBlobLeaf(proof.into()).from(sender);
+            return send_simulated(call, dry_run).await;
+        }
+        let addressed_message = proof.message.clone().ok_or(BridgeError::Decode(
+            "Proof does not contain a message".into(),
+        ))?;
+        let message: AvailBridgeContract::Message = addressed_message.clone().into();
+        match addressed_message.message {
+            CoreMessage::ArbitraryMessage(_) => {
+                let call = contract.receiveMessage(message, proof.into()).from(sender);
+                send_simulated(call, dry_run).await
+            }
+            CoreMessage::FungibleToken { asset_id, .. } if asset_id == AVAIL_ASSET_ID => {
+                let call = contract.receiveAVAIL(message, proof.into()).from(sender);
+                send_simulated(call, dry_run).await
+            }
+            CoreMessage::FungibleToken { asset_id, .. } if asset_id == ETH_ASSET_ID => {
+                let call = contract.receiveETH(message, proof.into()).from(sender);
+                send_simulated(call, dry_run).await
+            }
+            CoreMessage::FungibleToken { .. } => {
+                let call = contract.receiveERC20(message, proof.into()).from(sender);
+                send_simulated(call, dry_run).await
+            }
+        }
+    }
+}
+
+fn transfer_span(kind: AvailToEthKind) -> Span {
+    info_span!(
+        "avail_to_eth_transfer",
+        ?kind,
+        avail_tx_hash = Empty,
+        avail_block_hash = Empty,
+        extrinsic_index = Empty,
+        message_id = Empty,
+        eth_tx_hash = Empty,
+    )
+}
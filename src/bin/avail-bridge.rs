@@ -0,0 +1,879 @@
+use alloy::primitives::{Address, B256};
+use alloy_provider::ProviderBuilder;
+use alloy_signer_local::PrivateKeySigner;
+use anyhow::{anyhow, Result};
+use avail_bridge_tools::attestation::{AttestationCertificate, CertificateError};
+use avail_bridge_tools::avail_to_eth::{
+    bridge_avail_to_eth, submit_data_for_attestation, AvailToEthKind, AvailToEthTransfer,
+    ClaimOutcome,
+};
+use avail_bridge_tools::eth_to_avail::{send_eth_to_avail, EthToAvailTransfer};
+use avail_bridge_tools::journal::{self, resume_pending, Journal, ResumeReport};
+use avail_bridge_tools::merkle::ProofVerificationError;
+use avail_bridge_tools::metrics;
+use avail_bridge_tools::relayer::{
+    AvailToEthRelayer, EthToAvailRelayer, MessageFilter, MessageType, DEFAULT_CONCURRENCY,
+};
+use avail_bridge_tools::status::{claim_status, ClaimStatus, ExecutionStatus};
+use avail_bridge_tools::tokens::AVAIL_ASSET_ID;
+use avail_bridge_tools::vectorx::HeadTracker;
+use avail_bridge_tools::{
+    address_to_h256, connect_avail, init_tracing, AvailBridgeContract, BridgeApiClient,
+    BridgeApiError, BridgeError, Config,
+};
+use avail_rust::avail::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+use avail_rust::avail::vector::calls::types::send_message::Message as AvailMessage;
+use avail_rust::avail_core::data_proof::Message;
+use avail_rust::subxt::utils::AccountId32;
+use avail_rust::{Keypair, SecretUri};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use reqwest::Url;
+use serde_json::{json, Value};
+use sp_core::H256;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_CONFIG: u8 = 3;
+const EXIT_BRIDGE_API: u8 = 4;
+const EXIT_INVALID_PROOF: u8 = 5;
+const EXIT_NOT_READY: u8 = 6;
+const EXIT_REVERTED: u8 = 7;
+
+/// Default time `resume` spends on one transfer before moving on to the next.
+const DEFAULT_ENTRY_TIMEOUT_SECS: u64 = 3600;
+
+#[derive(Parser)]
+#[command(
+    name = "avail-bridge",
+    version,
+    about = "Send and claim transfers over the Avail bridge"
+)]
+struct Cli {
+    /// Path to the config file
+    #[arg(short, long, global = true, default_value = "./config.toml")]
+    config: PathBuf,
+    /// Path to the transfer journal
+    #[arg(long, global = true, default_value = "./bridge-journal.json")]
+    journal: PathBuf,
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Send an arbitrary message
+    SendMessage {
+        direction: Direction,
+        /// Avail account (SS58 or hex public key) or Ethereum address
+        #[arg(long)]
+        recipient: String,
+        #[arg(long)]
+        data: String,
+        /// Treat `data` as hex encoded bytes
+        #[arg(long)]
+        hex: bool,
+        /// Return once the transfer is sent instead of waiting to claim it
+        #[arg(long)]
+        no_wait: bool,
+    },
+    /// Send AVAIL tokens
+    SendAvail {
+        direction: Direction,
+        /// Avail account (SS58 or hex public key) or Ethereum address
+        #[arg(long)]
+        recipient: String,
+        /// Amount in the smallest unit
+        #[arg(long)]
+        amount: u128,
+        /// Return once the transfer is sent instead of waiting to claim it
+        #[arg(long)]
+        no_wait: bool,
+    },
+    /// Submit data to Avail and attest it on Ethereum
+    Attest {
+        #[arg(long)]
+        data: String,
+        /// Treat `data` as hex encoded bytes
+        #[arg(long)]
+        hex: bool,
+        /// Return once the data is finalized on Avail
+        #[arg(long)]
+        no_wait: bool,
+        /// Send the `verifyBlobLeaf` check as a transaction instead of a view call. It writes no
+        /// state and emits no event, so its calldata is the only trace left on Ethereum
+        #[arg(long)]
+        on_chain: bool,
+        /// Write a portable attestation certificate to this path
+        #[arg(long, conflicts_with = "no_wait")]
+        certificate: Option<PathBuf>,
+    },
+    /// Claim a transfer that was already sent
+    Claim {
+        /// Only simulate the Ethereum claim and print its gas estimate
+        #[arg(long)]
+        dry_run: bool,
+        #[command(subcommand)]
+        transfer: TransferRef,
+    },
+    /// Show the stage of a transfer without changing it
+    Status {
+        #[command(subcommand)]
+        transfer: TransferRef,
+    },
+    /// Print the proof for a transfer
+    Proof {
+        #[command(subcommand)]
+        transfer: TransferRef,
+    },
+    /// Verify an attestation certificate offline, and against Ethereum if an RPC URL is given
+    VerifyCertificate {
+        path: PathBuf,
+        /// Ethereum RPC used to check the proof with the bridge contract
+        #[arg(long, requires = "bridge")]
+        ethereum_url: Option<Url>,
+        /// Address of the trusted bridge contract; the certificate must name it
+        #[arg(long)]
+        bridge: Option<Address>,
+    },
+    /// Show the latest Avail range committed by VectorX on Ethereum
+    Head,
+    /// Relay transfers as they are sent until stopped with Ctrl-C or SIGTERM
+    Relay {
+        #[command(subcommand)]
+        direction: RelayDirection,
+        /// Serve Prometheus metrics on this address instead of `metrics_address` from the config,
+        /// requires the `metrics` feature
+        #[arg(long, global = true)]
+        metrics_address: Option<SocketAddr>,
+    },
+    /// Drive every pending transfer in the journal to completion
+    Resume {
+        /// Seconds after which a transfer is left pending and the next one is resumed; 0 waits
+        /// for every transfer to complete
+        #[arg(long, default_value_t = DEFAULT_ENTRY_TIMEOUT_SECS)]
+        entry_timeout: u64,
+    },
+    /// List the transfers recorded in the journal
+    Journal {
+        /// Only list transfers that are not completed yet
+        #[arg(long)]
+        pending: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Direction {
+    AvailToEth,
+    EthToAvail,
+}
+
+#[derive(Subcommand)]
+enum TransferRef {
+    /// Avail -> Ethereum transfer, identified by its Avail block hash and extrinsic index
+    AvailToEth {
+        #[arg(long)]
+        block_hash: H256,
+        #[arg(long)]
+        index: u32,
+        /// The extrinsic is a `submit_data` blob instead of a bridge message
+        #[arg(long)]
+        blob: bool,
+    },
+    /// Ethereum -> Avail transfer, identified by its Ethereum transaction hash
+    EthToAvail {
+        #[arg(long)]
+        tx_hash: B256,
+    },
+}
+
+#[derive(Subcommand)]
+enum RelayDirection {
+    /// Claim messages sent from Avail on Ethereum
+    AvailToEth {
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Avail block to start scanning from when the journal has no relayer cursor yet
+        #[arg(long)]
+        start_block: Option<u64>,
+    },
+    /// Execute messages sent from Ethereum on Avail
+    EthToAvail {
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Ethereum block to start scanning from when the journal has no relayer cursor yet
+        #[arg(long)]
+        start_block: Option<u64>,
+    },
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// Only relay messages to this recipient, can be repeated
+    #[arg(long = "recipient")]
+    recipients: Vec<String>,
+    /// Only relay messages of this type, can be repeated
+    #[arg(long = "message-type", value_enum)]
+    message_types: Vec<MessageTypeArg>,
+    /// Number of transfers proved and claimed at the same time
+    #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+    concurrency: usize,
+}
+
+impl FilterArgs {
+    fn message_filter(&self, direction: Direction) -> Result<MessageFilter> {
+        Ok(MessageFilter {
+            recipients: self
+                .recipients
+                .iter()
+                .map(|recipient| parse_recipient(direction, recipient))
+                .collect::<Result<_>>()?,
+            message_types: self.message_types.iter().map(|t| (*t).into()).collect(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MessageTypeArg {
+    ArbitraryMessage,
+    FungibleToken,
+}
+
+impl From<MessageTypeArg> for MessageType {
+    fn from(value: MessageTypeArg) -> Self {
+        match value {
+            MessageTypeArg::ArbitraryMessage => MessageType::ArbitraryMessage,
+            MessageTypeArg::FungibleToken => MessageType::FungibleToken,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct NotReady(String);
+
+impl fmt::Display for NotReady {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Not ready: {}", self.0)
+    }
+}
+
+impl std::error::Error for NotReady {}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_tracing();
+    match run(&cli).await {
+        Ok(output) => {
+            print_output(cli.json, &output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", json!({ "error": format!("{e:#}") }));
+            } else {
+                eprintln!("Error: {e:#}");
+            }
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+fn exit_code(e: &anyhow::Error) -> u8 {
+    if let Some(e) = e.downcast_ref::<BridgeError>() {
+        return match e {
+            BridgeError::Config(_) => EXIT_CONFIG,
+            BridgeError::BridgeApi(_) => EXIT_BRIDGE_API,
+            BridgeError::InvalidProof(_)
+            | BridgeError::InvalidCertificate(_)
+            | BridgeError::InvalidStorageProof(_) => EXIT_INVALID_PROOF,
+            BridgeError::Revert(_) | BridgeError::TransactionReverted(_) => EXIT_REVERTED,
+            _ => EXIT_FAILURE,
+        };
+    }
+    if e.downcast_ref::<BridgeApiError>().is_some() {
+        EXIT_BRIDGE_API
+    } else if e.downcast_ref::<ProofVerificationError>().is_some()
+        || e.downcast_ref::<CertificateError>().is_some()
+    {
+        EXIT_INVALID_PROOF
+    } else if e.downcast_ref::<NotReady>().is_some() {
+        EXIT_NOT_READY
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+fn print_output(json: bool, output: &Value) {
+    if json {
+        println!("{output}");
+        return;
+    }
+    match output {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value {
+                    Value::String(s) => println!("{key}: {s}"),
+                    other => println!("{key}: {other}"),
+                }
+            }
+        }
+        other => println!("{other:#}"),
+    }
+}
+
+fn load_config(path: &Path) -> Result<Config> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| BridgeError::Config(format!("Cannot read {}: {e}", path.display())))?;
+    let config = toml::from_str::<Config>(&content)
+        .map_err(|e| BridgeError::Config(format!("Cannot parse {}: {e}", path.display())))?;
+    Ok(config)
+}
+
+fn avail_signer(config: &Config) -> Result<Keypair> {
+    let secret_uri = SecretUri::from_str(config.avail_sender_mnemonic.as_str())
+        .map_err(|e| BridgeError::Config(format!("Invalid avail_sender_mnemonic: {e}")))?;
+    Ok(Keypair::from_uri(&secret_uri)
+        .map_err(|e| BridgeError::Config(format!("Invalid avail_sender_mnemonic: {e}")))?)
+}
+
+fn eth_signer(config: &Config) -> Result<PrivateKeySigner> {
+    Ok(config
+        .ethereum_secret
+        .parse::<PrivateKeySigner>()
+        .map_err(|e| BridgeError::Config(format!("Invalid ethereum_secret: {e}")))?)
+}
+
+fn parse_data(data: &str, is_hex: bool) -> Result<Vec<u8>> {
+    if is_hex {
+        Ok(hex::decode(data.trim_start_matches("0x"))?)
+    } else {
+        Ok(data.as_bytes().to_vec())
+    }
+}
+
+fn parse_recipient(direction: Direction, recipient: &str) -> Result<H256> {
+    match direction {
+        Direction::AvailToEth => {
+            if let Ok(address) = Address::from_str(recipient) {
+                return Ok(address_to_h256(address));
+            }
+            H256::from_str(recipient).map_err(|_| anyhow!("Invalid Ethereum recipient {recipient}"))
+        }
+        Direction::EthToAvail => {
+            if let Ok(public_key) = H256::from_str(recipient) {
+                return Ok(public_key);
+            }
+            let account = AccountId32::from_str(recipient)
+                .map_err(|e| anyhow!("Invalid Avail recipient {recipient}: {e:?}"))?;
+            Ok(H256(account.0))
+        }
+    }
+}
+
+async fn run(cli: &Cli) -> Result<Value> {
+    // Certificates are verified by third parties that have no config.
+    if let Command::VerifyCertificate {
+        path,
+        ethereum_url,
+        bridge,
+    } = &cli.command
+    {
+        return verify_certificate(path, ethereum_url.clone(), *bridge).await;
+    }
+    let config = load_config(&cli.config)?;
+    let mut journal = Journal::open(&cli.journal)?;
+    match &cli.command {
+        Command::SendMessage {
+            direction,
+            recipient,
+            data,
+            hex,
+            no_wait,
+        } => {
+            let recipient = parse_recipient(*direction, recipient)?;
+            let data = parse_data(data, *hex)?;
+            match direction {
+                Direction::AvailToEth => {
+                    let message = AvailMessage::ArbitraryMessage(BoundedVec(data));
+                    let mut transfer = bridge_avail_to_eth(
+                        &config,
+                        &avail_signer(&config)?,
+                        eth_signer(&config)?,
+                        message,
+                        recipient,
+                    )
+                    .await?;
+                    journal_avail_to_eth(&mut journal, &mut transfer, *no_wait, true).await
+                }
+                Direction::EthToAvail => {
+                    let message = Message::ArbitraryMessage(
+                        data.try_into()
+                            .map_err(|_| anyhow!("Message data too long"))?,
+                    );
+                    send_and_journal_eth_to_avail(
+                        &config,
+                        &mut journal,
+                        message,
+                        recipient,
+                        *no_wait,
+                    )
+                    .await
+                }
+            }
+        }
+        Command::SendAvail {
+            direction,
+            recipient,
+            amount,
+            no_wait,
+        } => {
+            let recipient = parse_recipient(*direction, recipient)?;
+            match direction {
+                Direction::AvailToEth => {
+                    let message = AvailMessage::FungibleToken {
+                        asset_id: AVAIL_ASSET_ID,
+                        amount: *amount,
+                    };
+                    let mut transfer = bridge_avail_to_eth(
+                        &config,
+                        &avail_signer(&config)?,
+                        eth_signer(&config)?,
+                        message,
+                        recipient,
+                    )
+                    .await?;
+                    journal_avail_to_eth(&mut journal, &mut transfer, *no_wait, true).await
+                }
+                Direction::EthToAvail => {
+                    let message = Message::FungibleToken {
+                        asset_id: AVAIL_ASSET_ID,
+                        amount: *amount,
+                    };
+                    send_and_journal_eth_to_avail(
+                        &config,
+                        &mut journal,
+                        message,
+                        recipient,
+                        *no_wait,
+                    )
+                    .await
+                }
+            }
+        }
+        Command::Attest {
+            data,
+            hex,
+            no_wait,
+            on_chain,
+            certificate,
+        } => {
+            let mut transfer = submit_data_for_attestation(
+                &config,
+                &avail_signer(&config)?,
+                eth_signer(&config)?,
+                parse_data(data, *hex)?,
+            )
+            .await?;
+            let mut output =
+                journal_avail_to_eth(&mut journal, &mut transfer, *no_wait, *on_chain).await?;
+            if let Some(path) = certificate {
+                let certificate = transfer.certificate().await?;
+                std::fs::write(path, certificate.to_json()?)?;
+                output["certificate"] = json!(path.display().to_string());
+            }
+            Ok(output)
+        }
+        Command::Claim { dry_run, transfer } => match transfer {
+            TransferRef::AvailToEth {
+                block_hash,
+                index,
+                blob,
+            } => {
+                let mut transfer = resume_avail_to_eth(&config, *block_hash, *index, *blob).await?;
+                if *dry_run {
+                    let gas_estimate = transfer.simulate_claim().await?;
+                    return Ok(json!({
+                        "stage": "simulated",
+                        "gasEstimate": gas_estimate.to_string(),
+                    }));
+                }
+                journal_avail_to_eth(&mut journal, &mut transfer, false, true).await
+            }
+            TransferRef::EthToAvail { .. } if *dry_run => Err(anyhow!(
+                "--dry-run is only supported for Avail -> Ethereum claims"
+            )),
+            TransferRef::EthToAvail { tx_hash } => {
+                let transfer = EthToAvailTransfer::resume(&config, *tx_hash).await?;
+                journal_eth_to_avail(&config, &mut journal, transfer, false).await
+            }
+        },
+        Command::Status { transfer } => match transfer {
+            TransferRef::AvailToEth {
+                block_hash,
+                index,
+                blob,
+            } => {
+                let mut transfer = resume_avail_to_eth(&config, *block_hash, *index, *blob).await?;
+                let mut output = json!({
+                    "blockHash": format!("{block_hash:?}"),
+                    "extrinsicIndex": index,
+                });
+                let Some(finalized) = transfer.try_finalized().await? else {
+                    output["stage"] = json!("included");
+                    return Ok(output);
+                };
+                output["blockNumber"] = json!(finalized.block_number);
+                let Some(range) = transfer.try_committed().await? else {
+                    output["stage"] = json!("finalized");
+                    return Ok(output);
+                };
+                output["range"] = json!(range);
+                let proof = transfer.proof().await?;
+                output["stage"] = match transfer.kind() {
+                    // Submitted data is never claimed, it is done once its leaf verifies.
+                    AvailToEthKind::Data if transfer.verify().await? => json!("verified"),
+                    AvailToEthKind::Data => json!("verification_failed"),
+                    AvailToEthKind::Message => {
+                        let message = proof.message.as_ref().ok_or(anyhow!(
+                            "Extrinsic sends no message, pass --blob for submitted data"
+                        ))?;
+                        let provider = ProviderBuilder::new().on_http(config.parse_ethereum_url()?);
+                        let contract =
+                            AvailBridgeContract::new(config.parse_contract_address()?, &provider);
+                        output["messageId"] = json!(message.id);
+                        match claim_status(&contract, message).await? {
+                            ClaimStatus::Claimable => json!("claimable"),
+                            ClaimStatus::AlreadyBridged => json!("claimed"),
+                        }
+                    }
+                };
+                Ok(output)
+            }
+            TransferRef::EthToAvail { tx_hash } => {
+                let mut transfer = EthToAvailTransfer::resume(&config, *tx_hash).await?;
+                let sent = transfer.sent().clone();
+                let mut output = json!({
+                    "txHash": sent.tx_hash.to_string(),
+                    "blockNumber": sent.block_number,
+                    "messageId": sent.message.id,
+                    "stage": "sent",
+                });
+                if let Some(stored) = transfer.try_stored().await? {
+                    output["stage"] = json!("stored");
+                    output["slot"] = json!(stored.slot);
+                }
+                match transfer.execution_status().await? {
+                    ExecutionStatus::NotExecuted => {}
+                    ExecutionStatus::Succeeded => output["stage"] = json!("executed"),
+                    ExecutionStatus::Failed => output["stage"] = json!("execution_failed"),
+                }
+                Ok(output)
+            }
+        },
+        Command::Proof { transfer } => match transfer {
+            TransferRef::AvailToEth {
+                block_hash,
+                index,
+                blob,
+            } => {
+                let mut transfer = resume_avail_to_eth(&config, *block_hash, *index, *blob).await?;
+                if transfer.try_committed().await?.is_none() {
+                    return Err(NotReady(format!(
+                        "Avail block {block_hash:?} is not committed by VectorX yet"
+                    ))
+                    .into());
+                }
+                Ok(json!(transfer.proof().await?))
+            }
+            TransferRef::EthToAvail { tx_hash } => {
+                let mut transfer = EthToAvailTransfer::resume(&config, *tx_hash).await?;
+                if transfer.try_stored().await?.is_none() {
+                    return Err(NotReady(format!(
+                        "Ethereum block {} is not stored on Avail yet",
+                        transfer.sent().block_number
+                    ))
+                    .into());
+                }
+                Ok(json!(transfer.proof().await?))
+            }
+        },
+        Command::VerifyCertificate { .. } => unreachable!("handled before loading the config"),
+        Command::Head => {
+            let provider = ProviderBuilder::new().on_http(config.parse_ethereum_url()?);
+            let contract = AvailBridgeContract::new(config.parse_contract_address()?, &provider);
+            let head_tracker = HeadTracker::for_bridge(&contract)
+                .await?
+                .with_log_scan(config.log_scan());
+            let head = head_tracker.head().await?;
+            Ok(json!({
+                "vectorx": head_tracker.address().to_string(),
+                "start": head.start + 1,
+                "end": head.end,
+                "rangeHash": format!("{:?}", head.range_hash()),
+                "dataCommitment": format!("{:?}", head.data_commitment),
+            }))
+        }
+        Command::Relay {
+            direction,
+            metrics_address,
+        } => {
+            let metrics_address = match metrics_address {
+                Some(address) => Some(*address),
+                None => config.parse_metrics_address()?,
+            };
+            let metrics_server = match metrics_address {
+                Some(address) => Some(metrics::serve(address).await?),
+                None => None,
+            };
+            let report = match direction {
+                RelayDirection::AvailToEth {
+                    filter,
+                    start_block,
+                } => {
+                    let mut relayer =
+                        AvailToEthRelayer::new(&config, eth_signer(&config)?, journal)
+                            .with_filter(filter.message_filter(Direction::AvailToEth)?)
+                            .with_concurrency(filter.concurrency);
+                    if let Some(start_block) = start_block {
+                        relayer = relayer.with_start_block(*start_block);
+                    }
+                    report_json(relayer.run(shutdown_signal()).await?)
+                }
+                RelayDirection::EthToAvail {
+                    filter,
+                    start_block,
+                } => {
+                    let mut relayer =
+                        EthToAvailRelayer::new(&config, avail_signer(&config)?, journal)
+                            .with_filter(filter.message_filter(Direction::EthToAvail)?)
+                            .with_concurrency(filter.concurrency);
+                    if let Some(start_block) = start_block {
+                        relayer = relayer.with_start_block(*start_block);
+                    }
+                    report_json(relayer.run(shutdown_signal()).await?)
+                }
+            };
+            if let Some(metrics_server) = metrics_server {
+                metrics_server.abort();
+            }
+            report
+        }
+        Command::Resume { entry_timeout } => {
+            let report = resume_pending(
+                &mut journal,
+                &config,
+                &avail_signer(&config)?,
+                eth_signer(&config)?,
+                (*entry_timeout > 0).then(|| Duration::from_secs(*entry_timeout)),
+            )
+            .await?;
+            report_json(report)
+        }
+        Command::Journal { pending } => {
+            if *pending {
+                Ok(json!(journal.pending().collect::<Vec<_>>()))
+            } else {
+                Ok(json!(journal.entries()))
+            }
+        }
+    }
+}
+
+fn report_json(report: ResumeReport) -> Result<Value> {
+    Ok(json!({
+        "completed": report.completed,
+        "failed": report.failed.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+    }))
+}
+
+/// Completes on Ctrl-C, or on SIGTERM on Unix.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+async fn verify_certificate(
+    path: &Path,
+    ethereum_url: Option<Url>,
+    bridge: Option<Address>,
+) -> Result<Value> {
+    let content = std::fs::read_to_string(path)?;
+    let certificate = AttestationCertificate::from_json(&content)?;
+    let on_chain = ethereum_url.is_some();
+    match (ethereum_url, bridge) {
+        (Some(url), Some(bridge)) => certificate.verify_on_chain(url, bridge).await?,
+        (Some(_), None) => return Err(anyhow!("--ethereum-url requires --bridge")),
+        (None, _) => certificate.verify()?,
+    }
+    Ok(json!({
+        "dataHash": format!("{:?}", certificate.data_hash),
+        "blockHash": format!("{:?}", certificate.block_hash),
+        "blockNumber": certificate.block_number,
+        "extrinsicIndex": certificate.extrinsic_index,
+        "verified": true,
+        "onChain": on_chain,
+    }))
+}
+
+async fn resume_avail_to_eth(
+    config: &Config,
+    block_hash: H256,
+    index: u32,
+    blob: bool,
+) -> Result<AvailToEthTransfer> {
+    let kind = if blob {
+        AvailToEthKind::Data
+    } else {
+        AvailToEthKind::Message
+    };
+    let sdk = connect_avail(&config.avail_rpc_url).await?;
+    Ok(AvailToEthTransfer::resume(
+        config,
+        sdk,
+        eth_signer(config)?,
+        kind,
+        block_hash,
+        index,
+    )?)
+}
+
+/// Journals an Avail -> Ethereum transfer, or picks up its existing entry, and drives it,
+/// recording a failure on the entry.
+async fn journal_avail_to_eth(
+    journal: &mut Journal,
+    transfer: &mut AvailToEthTransfer,
+    no_wait: bool,
+    on_chain: bool,
+) -> Result<Value> {
+    let id = journal.record_or_update_avail_to_eth(transfer, on_chain)?;
+    let result = drive_journaled_avail_to_eth(journal, id, transfer, no_wait, on_chain).await;
+    if let Err(e) = &result {
+        journal.record_error(id, e)?;
+    }
+    result
+}
+
+async fn drive_journaled_avail_to_eth(
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut AvailToEthTransfer,
+    no_wait: bool,
+    on_chain: bool,
+) -> Result<Value> {
+    let finalized = journal::drive_avail_to_eth_finalization(journal, id, transfer).await?;
+    let mut output = json!({
+        "blockHash": format!("{:?}", finalized.block_hash),
+        "blockNumber": finalized.block_number,
+        "extrinsicIndex": finalized.extrinsic_index,
+        "stage": "finalized",
+    });
+    if no_wait {
+        return Ok(output);
+    }
+
+    if on_chain {
+        match journal::drive_avail_to_eth(journal, id, transfer).await? {
+            ClaimOutcome::Claimed(receipt) => {
+                output["claimTxHash"] = json!(receipt.transaction_hash.to_string());
+            }
+            ClaimOutcome::AlreadyBridged => output["alreadyBridged"] = json!(true),
+        }
+    } else {
+        let verified = journal::drive_avail_to_eth_verification(journal, id, transfer).await?;
+        output["verified"] = json!(verified);
+    }
+    // The stages are done, so these return their cached results.
+    output["range"] = json!(transfer.committed().await?);
+    if let Some(message_id) = transfer.message_id() {
+        output["messageId"] = json!(message_id);
+    }
+    output["stage"] = json!(transfer.stage());
+    Ok(output)
+}
+
+/// Sends `message` to Avail and journals the transfer as soon as the Ethereum transaction is
+/// broadcast, before waiting for it to be mined.
+async fn send_and_journal_eth_to_avail(
+    config: &Config,
+    journal: &mut Journal,
+    message: Message,
+    recipient: H256,
+    no_wait: bool,
+) -> Result<Value> {
+    let tx_hash = send_eth_to_avail(config, eth_signer(config)?, message, recipient).await?;
+    let id = journal.record_sent_eth_to_avail(tx_hash)?;
+    let result = match EthToAvailTransfer::sent_in(config, tx_hash).await {
+        Ok(mut transfer) => {
+            drive_journaled_eth_to_avail(config, journal, id, &mut transfer, no_wait).await
+        }
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = &result {
+        journal.record_error(id, e)?;
+    }
+    result
+}
+
+/// Journals an Ethereum -> Avail transfer that is already mined, or picks up its existing
+/// entry, and drives it, recording a failure on the entry.
+async fn journal_eth_to_avail(
+    config: &Config,
+    journal: &mut Journal,
+    mut transfer: EthToAvailTransfer,
+    no_wait: bool,
+) -> Result<Value> {
+    let id = journal.record_or_update_eth_to_avail(&transfer)?;
+    let result = drive_journaled_eth_to_avail(config, journal, id, &mut transfer, no_wait).await;
+    if let Err(e) = &result {
+        journal.record_error(id, e)?;
+    }
+    result
+}
+
+async fn drive_journaled_eth_to_avail(
+    config: &Config,
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut EthToAvailTransfer,
+    no_wait: bool,
+) -> Result<Value> {
+    let sent = transfer.sent().clone();
+    let mut output = json!({
+        "txHash": sent.tx_hash.to_string(),
+        "blockNumber": sent.block_number,
+        "messageId": sent.message.id,
+        "stage": "sent",
+    });
+    if no_wait {
+        journal.update_eth_to_avail(id, transfer)?;
+        return Ok(output);
+    }
+
+    let executed =
+        journal::drive_eth_to_avail(journal, id, transfer, &avail_signer(config)?).await?;
+    output["slot"] = json!(transfer.stored().await?.slot);
+    output["stage"] = json!("executed");
+    match executed {
+        Some(block_hash) => output["executedBlockHash"] = json!(format!("{block_hash:?}")),
+        None => output["alreadyExecuted"] = json!(true),
+    }
+    Ok(output)
+}
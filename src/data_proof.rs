@@ -0,0 +1,117 @@
+use crate::merkle::sha2_merkle_proof;
+use crate::vectorx::{CommittedRange, HeadTracker, LogScan};
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::{BridgeApiMerkleProof, BridgeError, Result};
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use avail_rust::avail::runtime_types::avail_core::header::extension::HeaderExtension;
+use avail_rust::SDK;
+use futures::{stream, StreamExt, TryStreamExt};
+use sp_core::H256;
+
+/// Number of Avail headers fetched at once when rebuilding a commitment tree.
+const HEADER_FETCH_CONCURRENCY: usize = 32;
+
+/// Builds the proof for the extrinsic at `extrinsic_index` in the Avail block `block_hash` from
+/// an Avail node and the VectorX contract used by `bridge`, without going through the Bridge
+/// API. The leaf proof comes from `kate_queryDataProof`, the data root proof is rebuilt from the
+/// data roots of the headers in the committed range and checked against the commitment stored
+/// in VectorX. The committed range is searched within the limits of `log_scan`.
+pub async fn build_eth_proof<T, P>(
+    sdk: &SDK,
+    bridge: &AvailBridgeContractInstance<T, P>,
+    block_hash: H256,
+    extrinsic_index: u32,
+    log_scan: LogScan,
+) -> Result<BridgeApiMerkleProof>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let block_number = sdk.api.blocks().at(block_hash).await?.number() as u64;
+    let vectorx_address = bridge.vectorx().call().await?._0;
+    let head_tracker = HeadTracker::new(vectorx_address, bridge.provider()).with_log_scan(log_scan);
+    let range = head_tracker
+        .committed_range(block_number)
+        .await?
+        .ok_or(BridgeError::Transfer(format!(
+            "Block {block_number} is not committed by VectorX yet"
+        )))?;
+
+    let response = sdk
+        .rpc
+        .kate
+        .query_data_proof(extrinsic_index, Some(block_hash))
+        .await?;
+    let data_proof = response.data_proof;
+
+    let (data_root_index, data_root_proof) = data_root_proof(sdk, &range, block_number).await?;
+    let range_hash = range.range_hash();
+    let commitment = head_tracker.data_root_commitment(&range).await?;
+    if commitment != range.data_commitment {
+        return Err(BridgeError::Transfer(format!(
+            "VectorX stores commitment {commitment:?} for range {}..={}, event has {:?}",
+            range.start, range.end, range.data_commitment
+        )));
+    }
+
+    let proof = BridgeApiMerkleProof {
+        blob_root: data_proof.roots.blob_root,
+        block_hash,
+        bridge_root: data_proof.roots.bridge_root,
+        data_root: data_proof.roots.data_root,
+        data_root_commitment: range.data_commitment,
+        data_root_index,
+        data_root_proof,
+        leaf: data_proof.leaf,
+        leaf_index: data_proof.leaf_index,
+        leaf_proof: data_proof.proof,
+        message: response.message,
+        range_hash,
+    };
+    proof.verify()?;
+    Ok(proof)
+}
+
+/// Rebuilds the VectorX data root commitment tree of `range` from Avail headers and returns the
+/// index and proof of the data root of `block_number`.
+async fn data_root_proof(
+    sdk: &SDK,
+    range: &CommittedRange,
+    block_number: u64,
+) -> Result<(u32, Vec<H256>)> {
+    let index = range
+        .data_root_index(block_number)
+        .ok_or(BridgeError::Transfer(format!(
+            "Block {block_number} is not in range {}..={}",
+            range.start, range.end
+        )))?;
+    let mut data_roots: Vec<H256> = stream::iter(range.start + 1..=range.end)
+        .map(|number| header_data_root(sdk, number))
+        .buffered(HEADER_FETCH_CONCURRENCY)
+        .try_collect()
+        .await?;
+    data_roots.resize(range.tree_size as usize, H256::zero());
+
+    let (root, proof) = sha2_merkle_proof(&data_roots, index as usize).ok_or(
+        BridgeError::Decode(format!("Invalid tree size {}", range.tree_size)),
+    )?;
+    if root != range.data_commitment {
+        return Err(BridgeError::Transfer(format!(
+            "Data roots of blocks {}..={} hash to {root:?}, VectorX committed {:?}",
+            range.start + 1,
+            range.end,
+            range.data_commitment
+        )));
+    }
+    Ok((index, proof))
+}
+
+async fn header_data_root(sdk: &SDK, block_number: u32) -> Result<H256> {
+    let block_hash = sdk.rpc.chain.get_block_hash(Some(block_number)).await?;
+    let block = sdk.api.blocks().at(block_hash).await?;
+    // V3 is the only header extension of the runtime, so a new version fails to compile here
+    // instead of being misread.
+    let HeaderExtension::V3(extension) = &block.header().extension;
+    Ok(extension.commitment.data_root)
+}
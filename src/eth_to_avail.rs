@@ -0,0 +1,570 @@
+use crate::events::parse_message_sent;
+use crate::fee::quote_message_fee;
+use crate::head_watcher::{Head, HeadNotifications, HeadWatcher, StoredEthHead};
+use crate::merkle::message_leaf;
+use crate::metrics;
+use crate::status::{execution_status, sent_status, ExecutionStatus, SentStatus};
+use crate::storage_proof::{fetch_message_proof, message_storage_slot};
+use crate::tokens::{ensure_allowance, token_address, AVAIL_ASSET_ID, ETH_ASSET_ID};
+use crate::{
+    address_to_h256, connect_avail, convert_addressed_message, AccountStorageProof,
+    AvailBridgeContract, BridgeApiClient, BridgeError, Config, ProofSource, Result, AVAIL_DOMAIN,
+    DEFAULT_POLL_INTERVAL, ETHEREUM_DOMAIN,
+};
+use alloy::primitives::{B256, U256};
+use alloy_network::EthereumWallet;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::SolInterface;
+use avail_rust::avail::runtime_types::bounded_collections::bounded_vec::BoundedVec;
+use avail_rust::avail_core::data_proof::{AddressedMessage, Message};
+use avail_rust::subxt::utils::AccountId32;
+use avail_rust::{avail, AvailExtrinsicParamsBuilder, Keypair, WaitFor, SDK};
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::field::Empty;
+use tracing::{info, info_span, instrument, warn, Span};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum EthToAvailStage {
+    Sent,
+    Stored,
+    ProofFetched,
+    Executed,
+}
+
+#[derive(Debug, Clone)]
+pub struct SentMessage {
+    pub tx_hash: B256,
+    pub block_number: u64,
+    pub message: AddressedMessage,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StoredSlot {
+    pub slot: u64,
+    pub block_hash: H256,
+    pub block_number: u64,
+}
+
+/// Hands out the nonces of one Avail account, so executions can be submitted while earlier ones
+/// are still waiting for finalization. Submissions are serialized, and a failed one makes the
+/// next submission read the nonce from the chain again.
+#[derive(Debug, Default)]
+pub struct NonceTracker {
+    next: tokio::sync::Mutex<Option<u64>>,
+}
+
+impl NonceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the tracked nonce, e.g. after a submitted transaction was dropped.
+    pub async fn reset(&self) {
+        *self.next.lock().await = None;
+    }
+}
+
+/// Handle to an Ethereum -> Avail transfer. It can be created by sending a new message or by
+/// resuming from the hash of an Ethereum transaction that already sent one.
+pub struct EthToAvailTransfer {
+    config: Config,
+    /// Avail client, connected on first use unless one is passed with `with_sdk`.
+    sdk: OnceCell<SDK>,
+    stored_head: OnceCell<StoredEthHead>,
+    bridge_api: BridgeApiClient,
+    poll_interval: Duration,
+    sent: SentMessage,
+    stored: Option<StoredSlot>,
+    proof: Option<AccountStorageProof>,
+    executed: Option<H256>,
+    span: Span,
+}
+
+/// Time between checks for the receipt of a sent Ethereum transaction, one slot.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// Sends `message` to `recipient` on Avail, using `sendMessage` for arbitrary messages (with the
+/// fee quoted by the contract attached),
+/// `sendAVAIL` for AVAIL, `sendETH` for native ETH and `sendERC20` for other registered assets.
+pub async fn bridge_eth_to_avail(
+    config: &Config,
+    eth_signer: PrivateKeySigner,
+    message: Message,
+    recipient: H256,
+) -> Result<EthToAvailTransfer> {
+    let tx_hash = send_eth_to_avail(config, eth_signer, message, recipient).await?;
+    let transfer = EthToAvailTransfer::sent_in(config, tx_hash)
+        .await
+        .inspect_err(metrics::record_revert)?;
+    metrics::eth_to_avail_stage(EthToAvailStage::Sent);
+    transfer.span.in_scope(|| info!("Sent to Ethereum"));
+    Ok(transfer)
+}
+
+/// Broadcasts the Ethereum transaction of `bridge_eth_to_avail` and returns its hash without
+/// waiting for it to be mined, so it can be journaled first. `EthToAvailTransfer::sent_in`
+/// picks the transfer up from the hash.
+pub async fn send_eth_to_avail(
+    config: &Config,
+    eth_signer: PrivateKeySigner,
+    message: Message,
+    recipient: H256,
+) -> Result<B256> {
+    let sender = eth_signer.address();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(eth_signer))
+        .on_http(config.parse_ethereum_url()?);
+    let contract_address = config.parse_contract_address()?;
+    let contract = AvailBridgeContract::new(contract_address, &provider);
+
+    let pending_tx = match &message {
+        Message::ArbitraryMessage(data) => {
+            let quote = quote_message_fee(&contract, data.len(), config.fee_margin_percent).await?;
+            contract
+                .sendMessage(recipient.0.into(), data.to_vec().into())
+                .value(quote.total)
+                .from(sender)
+                .send()
+                .await?
+        }
+        Message::FungibleToken { asset_id, amount } if *asset_id == AVAIL_ASSET_ID => {
+            contract
+                .sendAVAIL(recipient.0.into(), U256::from(*amount))
+                .from(sender)
+                .send()
+                .await?
+        }
+        Message::FungibleToken { asset_id, amount } if *asset_id == ETH_ASSET_ID => {
+            contract
+                .sendETH(recipient.0.into())
+                .value(U256::from(*amount))
+                .from(sender)
+                .send()
+                .await?
+        }
+        Message::FungibleToken { asset_id, amount } => {
+            let token = token_address(&contract, *asset_id).await?;
+            ensure_allowance(
+                &provider,
+                token,
+                sender,
+                contract_address,
+                U256::from(*amount),
+            )
+            .await?;
+            contract
+                .sendERC20(asset_id.0.into(), recipient.0.into(), U256::from(*amount))
+                .from(sender)
+                .send()
+                .await?
+        }
+    };
+    Ok(*pending_tx.tx_hash())
+}
+
+impl EthToAvailTransfer {
+    pub fn from_sent(config: &Config, sent: SentMessage) -> Result<Self> {
+        let span = info_span!(
+            "eth_to_avail_transfer",
+            message_id = sent.message.id,
+            eth_tx_hash = %sent.tx_hash,
+            eth_block_number = sent.block_number,
+            avail_block_hash = Empty,
+        );
+        Ok(Self {
+            config: config.clone(),
+            sdk: OnceCell::new(),
+            stored_head: OnceCell::new(),
+            bridge_api: BridgeApiClient::new(config.bridge_api_url.as_str())?,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            sent,
+            stored: None,
+            proof: None,
+            executed: None,
+            span,
+        })
+    }
+
+    /// Rebuilds a transfer from the hash of an Ethereum transaction that called `sendMessage`,
+    /// `sendAVAIL`, `sendETH` or `sendERC20`, so claiming on Avail can continue without sending again.
+    pub async fn resume(config: &Config, tx_hash: B256) -> Result<Self> {
+        let provider = ProviderBuilder::new().on_http(config.parse_ethereum_url()?);
+        let contract_address = config.parse_contract_address()?;
+        let tx = provider
+            .get_transaction_by_hash(tx_hash)
+            .await?
+            .ok_or(BridgeError::Transfer(format!(
+                "Transaction {tx_hash} not found"
+            )))?;
+        if tx.to != Some(contract_address) {
+            return Err(BridgeError::Transfer(format!(
+                "Transaction {tx_hash} was not sent to the bridge"
+            )));
+        }
+        let receipt =
+            provider
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or(BridgeError::Transfer(format!(
+                    "Transaction {tx_hash} is not included yet"
+                )))?;
+        if !receipt.status() {
+            return Err(BridgeError::TransactionReverted(tx_hash));
+        }
+
+        let (recipient, message) =
+            match AvailBridgeContract::AvailBridgeContractCalls::abi_decode(&tx.input, true)? {
+                AvailBridgeContract::AvailBridgeContractCalls::sendMessage(call) => (
+                    call.recipient,
+                    Message::ArbitraryMessage(
+                        call.data
+                            .to_vec()
+                            .try_into()
+                            .map_err(|_| BridgeError::Decode("Message data too long".into()))?,
+                    ),
+                ),
+                AvailBridgeContract::AvailBridgeContractCalls::sendAVAIL(call) => (
+                    call.recipient,
+                    Message::FungibleToken {
+                        asset_id: H256::zero(),
+                        amount: call
+                            .amount
+                            .try_into()
+                            .map_err(|_| BridgeError::Decode("Amount too large".into()))?,
+                    },
+                ),
+                AvailBridgeContract::AvailBridgeContractCalls::sendERC20(call) => (
+                    call.recipient,
+                    Message::FungibleToken {
+                        asset_id: H256(call.assetId.0),
+                        amount: call
+                            .amount
+                            .try_into()
+                            .map_err(|_| BridgeError::Decode("Amount too large".into()))?,
+                    },
+                ),
+                AvailBridgeContract::AvailBridgeContractCalls::sendETH(call) => (
+                    call.recipient,
+                    Message::FungibleToken {
+                        asset_id: ETH_ASSET_ID,
+                        amount: tx
+                            .value
+                            .try_into()
+                            .map_err(|_| BridgeError::Decode("Amount too large".into()))?,
+                    },
+                ),
+                _ => {
+                    return Err(BridgeError::Transfer(format!(
+                        "Transaction {tx_hash} did not send a message"
+                    )))
+                }
+            };
+
+        let sent = SentMessage {
+            tx_hash,
+            block_number: receipt
+                .block_number
+                .ok_or(BridgeError::Decode("No block number!".into()))?,
+            message: AddressedMessage {
+                message,
+                from: address_to_h256(tx.from),
+                to: H256(recipient.0),
+                origin_domain: ETHEREUM_DOMAIN,
+                destination_domain: AVAIL_DOMAIN,
+                id: parse_message_sent(&receipt, contract_address)?.message_id,
+            },
+        };
+        let contract = AvailBridgeContract::new(contract_address, &provider);
+        let status = sent_status(&contract, &sent.message).await?;
+        if status != SentStatus::Sent {
+            return Err(BridgeError::Transfer(format!(
+                "Message {} does not match the bridge: {status:?}",
+                sent.message.id
+            )));
+        }
+        Self::from_sent(config, sent)
+    }
+
+    /// Waits for the transaction `tx_hash`, e.g. from `send_eth_to_avail`, to be mined and then
+    /// rebuilds the transfer like `resume`. Fails if the node no longer knows the transaction.
+    pub async fn sent_in(config: &Config, tx_hash: B256) -> Result<Self> {
+        let provider = ProviderBuilder::new().on_http(config.parse_ethereum_url()?);
+        while provider.get_transaction_receipt(tx_hash).await?.is_none() {
+            if provider.get_transaction_by_hash(tx_hash).await?.is_none() {
+                return Err(BridgeError::Transfer(format!(
+                    "Transaction {tx_hash} was dropped"
+                )));
+            }
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+        Self::resume(config, tx_hash).await
+    }
+
+    /// Sets the longest wait between checks of the Ethereum head stored on Avail.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Uses `sdk` for Avail instead of connecting, so transfers can share one client.
+    pub fn with_sdk(mut self, sdk: SDK) -> Self {
+        self.sdk = OnceCell::from(sdk);
+        self
+    }
+
+    async fn sdk(&self) -> Result<&SDK> {
+        self.sdk
+            .get_or_try_init(|| connect_avail(&self.config.avail_rpc_url))
+            .await
+    }
+
+    async fn stored_head(&self) -> Result<&StoredEthHead> {
+        self.stored_head
+            .get_or_try_init(|| async {
+                let stored_head = StoredEthHead::new(
+                    self.sdk().await?.clone(),
+                    self.config.parse_ethereum_url()?,
+                )
+                .with_beacon_genesis_time(self.config.beacon_genesis_time);
+                Ok::<_, BridgeError>(stored_head)
+            })
+            .await
+    }
+
+    pub fn sent(&self) -> &SentMessage {
+        &self.sent
+    }
+
+    pub fn message_id(&self) -> u64 {
+        self.sent.message.id
+    }
+
+    /// Hash of the Avail block the message was executed in.
+    pub fn executed_block_hash(&self) -> Option<H256> {
+        self.executed
+    }
+
+    /// Span of the transfer, carrying its message id and hashes. Each stage runs in a child span
+    /// named after it.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn stage(&self) -> EthToAvailStage {
+        if self.executed.is_some() {
+            EthToAvailStage::Executed
+        } else if self.proof.is_some() {
+            EthToAvailStage::ProofFetched
+        } else if self.stored.is_some() {
+            EthToAvailStage::Stored
+        } else {
+            EthToAvailStage::Sent
+        }
+    }
+
+    /// Waits until Avail has stored an Ethereum head at or after the sending block, woken up by
+    /// finalized Avail blocks that update the head.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn stored(&mut self) -> Result<StoredSlot> {
+        if let Some(stored) = self.stored {
+            return Ok(stored);
+        }
+        let notifications = HeadNotifications::avail_head_updates(self.sdk().await?)
+            .await
+            .inspect_err(|e| warn!(error = %e, "Avail head subscription failed, polling"))
+            .ok();
+        info!("Waiting for Avail to store the Ethereum block");
+        let stored = HeadWatcher::new(self.stored_head().await?, self.poll_interval)
+            .with_notifications(notifications)
+            .wait_until_covers(self.sent.block_number)
+            .await?;
+        info!(slot = stored.slot, "Stored on Avail");
+        metrics::eth_to_avail_stage(EthToAvailStage::Stored);
+        self.stored = Some(stored);
+        Ok(stored)
+    }
+
+    /// Checks once whether Avail has stored a head covering the sending block, without waiting.
+    #[instrument(name = "stored", parent = &self.span, skip_all)]
+    pub async fn try_stored(&mut self) -> Result<Option<StoredSlot>> {
+        if let Some(stored) = self.stored {
+            return Ok(Some(stored));
+        }
+        let stored = self
+            .stored_head()
+            .await?
+            .covering(self.sent.block_number)
+            .await?;
+        if let Some(stored) = stored {
+            info!(slot = stored.slot, "Stored on Avail");
+            metrics::eth_to_avail_stage(EthToAvailStage::Stored);
+        }
+        self.stored = stored;
+        Ok(stored)
+    }
+
+    /// Fetches the account and storage proofs for the message at the stored head, from the
+    /// Bridge API or with `eth_getProof` depending on `proof_source`. The head is read from Avail
+    /// storage either way, so `proof_source = "rpc"` does not depend on the Bridge API.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn proof(&mut self) -> Result<AccountStorageProof> {
+        if let Some(proof) = &self.proof {
+            return Ok(proof.clone());
+        }
+        let stored = self.stored().await?;
+        let started = Instant::now();
+        let proof = match self.config.proof_source {
+            ProofSource::BridgeApi => {
+                self.bridge_api
+                    .avl_proof(stored.block_hash, self.sent.message.id)
+                    .await?
+            }
+            ProofSource::Rpc => {
+                fetch_message_proof(
+                    self.config.parse_ethereum_url()?,
+                    self.config.parse_contract_address()?,
+                    stored.block_hash,
+                    self.sent.message.id,
+                    self.config.message_mapping_slot,
+                )
+                .await?
+            }
+        };
+        info!(source = ?self.config.proof_source, "Proof fetched");
+        metrics::eth_to_avail_proof_fetched(self.config.proof_source, started.elapsed());
+        metrics::eth_to_avail_stage(EthToAvailStage::ProofFetched);
+        self.proof = Some(proof.clone());
+        Ok(proof)
+    }
+
+    /// Verifies the account and storage proofs against the execution state root Avail stored
+    /// for the slot, checking that the bridge's `isSent` slot holds the message leaf.
+    #[instrument(parent = &self.span, skip_all)]
+    pub async fn verify_proof(&mut self) -> Result<()> {
+        let stored = self.stored().await?;
+        let proof = self.proof().await?;
+        self.verify_proof_with(stored, &proof).await
+    }
+
+    async fn verify_proof_with(
+        &self,
+        stored: StoredSlot,
+        proof: &AccountStorageProof,
+    ) -> Result<()> {
+        let state_root = self
+            .sdk()
+            .await?
+            .api
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&avail::storage().vector().execution_state_roots(stored.slot))
+            .await?
+            .ok_or(BridgeError::Transfer(format!(
+                "No execution state root stored for slot {}",
+                stored.slot
+            )))?;
+        proof.verify(
+            B256::from(state_root.0),
+            self.config.parse_contract_address()?,
+            message_storage_slot(self.sent.message.id, self.config.message_mapping_slot),
+            B256::from(message_leaf(&self.sent.message).0),
+        )?;
+        Ok(())
+    }
+
+    /// Checks whether the message was already executed on Avail, e.g. by another relayer.
+    pub async fn execution_status(&self) -> Result<ExecutionStatus> {
+        execution_status(self.sdk().await?, &self.sent.message).await
+    }
+
+    /// Executes the message on Avail and returns the hash of the finalized block. The proofs
+    /// are verified locally first, so no fees are spent on a proof Avail would reject.
+    pub async fn execute(&mut self, avail_signer: &Keypair) -> Result<H256> {
+        self.execute_with_nonces(avail_signer, &NonceTracker::new())
+            .await
+    }
+
+    /// Same as `execute`, taking the nonce from `nonces` so several messages can be executed
+    /// from the same account at once.
+    #[instrument(name = "execute", parent = &self.span, skip_all)]
+    pub async fn execute_with_nonces(
+        &mut self,
+        avail_signer: &Keypair,
+        nonces: &NonceTracker,
+    ) -> Result<H256> {
+        if let Some(block_hash) = self.executed {
+            return Ok(block_hash);
+        }
+        let stored = self.stored().await?;
+        let proof = self.proof().await?;
+        self.verify_proof_with(stored, &proof).await?;
+        let sdk = self.sdk().await?;
+
+        let acc_proof = BoundedVec(
+            proof
+                .account_proof
+                .into_iter()
+                .map(BoundedVec)
+                .collect::<Vec<_>>(),
+        );
+        let stor_proof = BoundedVec(
+            proof
+                .storage_proof
+                .into_iter()
+                .map(BoundedVec)
+                .collect::<Vec<_>>(),
+        );
+
+        let call = avail::tx().vector().execute(
+            stored.slot,
+            convert_addressed_message(self.sent.message.clone()),
+            acc_proof,
+            stor_proof,
+        );
+        let maybe_tx_progress = {
+            let mut next_nonce = nonces.next.lock().await;
+            let nonce = match *next_nonce {
+                Some(nonce) => nonce,
+                None => {
+                    let account = AccountId32(avail_signer.public_key().0);
+                    sdk.api.tx().account_nonce(&account).await?
+                }
+            };
+            let params = AvailExtrinsicParamsBuilder::new().nonce(nonce).build();
+            let maybe_tx_progress = sdk
+                .api
+                .tx()
+                .sign_and_submit_then_watch(&call, avail_signer, params)
+                .await;
+            *next_nonce = maybe_tx_progress.is_ok().then_some(nonce + 1);
+            maybe_tx_progress
+        };
+        let tx_in_block = match sdk
+            .util
+            .progress_transaction(maybe_tx_progress, WaitFor::BlockFinalization)
+            .await
+        {
+            Ok(tx_in_block) => tx_in_block,
+            Err(e) => {
+                nonces.reset().await;
+                return Err(BridgeError::Avail(format!("Transaction failed: {e}")));
+            }
+        };
+        tx_in_block.wait_for_success().await?;
+
+        let block_hash = tx_in_block.block_hash();
+        self.span
+            .record("avail_block_hash", tracing::field::debug(block_hash));
+        info!("Executed on Avail");
+        metrics::eth_to_avail_stage(EthToAvailStage::Executed);
+        self.executed = Some(block_hash);
+        Ok(block_hash)
+    }
+}
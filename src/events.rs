@@ -0,0 +1,57 @@
+use crate::{AvailBridgeContract, BridgeError, Result};
+use alloy::primitives::Address;
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_sol_types::SolEvent;
+use sp_core::H256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSent {
+    pub from: Address,
+    pub to: H256,
+    pub message_id: u64,
+}
+
+/// Finds the single `MessageSent` event emitted by `bridge` in `receipt`. Logs from other
+/// contracts (e.g. ERC20 transfers) are ignored.
+pub fn parse_message_sent(receipt: &TransactionReceipt, bridge: Address) -> Result<MessageSent> {
+    let logs = &receipt
+        .inner
+        .as_receipt()
+        .ok_or(BridgeError::Decode("Cannot convert to receipt".into()))?
+        .logs;
+    let mut events = logs
+        .iter()
+        .filter(|log| log.inner.address == bridge)
+        .filter(|log| {
+            log.inner.data.topics().first()
+                == Some(&AvailBridgeContract::MessageSent::SIGNATURE_HASH)
+        })
+        .map(|log| AvailBridgeContract::MessageSent::decode_log_data(&log.inner.data, true))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let event = match events.len() {
+        0 => {
+            return Err(BridgeError::Decode(format!(
+                "No MessageSent event from {bridge} in transaction {}",
+                receipt.transaction_hash
+            )))
+        }
+        1 => events.remove(0),
+        n => {
+            return Err(BridgeError::Decode(format!(
+                "Found {n} MessageSent events from {bridge} in transaction {}",
+                receipt.transaction_hash
+            )))
+        }
+    };
+    Ok(MessageSent {
+        from: event.from,
+        to: H256(event.to.0),
+        message_id: event.messageId.try_into().map_err(|_| {
+            BridgeError::Decode(format!(
+                "Message id {} does not fit in u64",
+                event.messageId
+            ))
+        })?,
+    })
+}
@@ -0,0 +1,51 @@
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::Result;
+use alloy::primitives::U256;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+
+/// Fee required by `sendMessage` for a payload, as quoted by the bridge contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub payload_len: usize,
+    pub fee_per_byte: U256,
+    /// Fee returned by `getFee`.
+    pub fee: U256,
+    pub margin_percent: u64,
+    /// Fee including the margin, attached as `msg.value`.
+    pub total: U256,
+}
+
+impl FeeQuote {
+    pub fn new(payload_len: usize, fee_per_byte: U256, fee: U256, margin_percent: u64) -> Self {
+        let margin = fee * U256::from(margin_percent) / U256::from(100);
+        Self {
+            payload_len,
+            fee_per_byte,
+            fee,
+            margin_percent,
+            total: fee + margin,
+        }
+    }
+}
+
+/// Quotes the `sendMessage` fee for a payload of `payload_len` bytes, adding `margin_percent`
+/// on top to absorb a `feePerByte` update between quoting and sending.
+pub async fn quote_message_fee<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    payload_len: usize,
+    margin_percent: u64,
+) -> Result<FeeQuote>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let fee_per_byte = bridge.feePerByte().call().await?._0;
+    let fee = bridge.getFee(U256::from(payload_len)).call().await?._0;
+    Ok(FeeQuote::new(
+        payload_len,
+        fee_per_byte,
+        fee,
+        margin_percent,
+    ))
+}
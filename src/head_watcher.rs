@@ -0,0 +1,304 @@
+use crate::eth_to_avail::StoredSlot;
+use crate::vectorx::{CommittedRange, HeadTracker, VectorX};
+use crate::{BridgeError, Result};
+use alloy::primitives::Address;
+use alloy_provider::{Provider, ProviderBuilder, WsConnect};
+use alloy_rpc_types_eth::{Block, BlockNumberOrTag, Filter};
+use alloy_sol_types::SolEvent;
+use alloy_transport::Transport;
+use avail_rust::{avail, SDK};
+use reqwest::Url;
+use sp_core::H256;
+use std::future::Future;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// First polling interval. Polling backs off from here up to the watcher's maximum interval.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Head that one chain keeps of the other: VectorX on Ethereum following Avail, or the vector
+/// pallet on Avail following Ethereum.
+pub trait Head {
+    type Covered;
+
+    /// Returns what covers `block_number`, or `None` if the head has not reached it yet.
+    fn covering(&self, block_number: u64) -> impl Future<Output = Result<Option<Self::Covered>>>;
+}
+
+impl<H: Head> Head for &H {
+    type Covered = H::Covered;
+
+    async fn covering(&self, block_number: u64) -> Result<Option<H::Covered>> {
+        (**self).covering(block_number).await
+    }
+}
+
+impl<T, P> Head for HeadTracker<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    type Covered = CommittedRange;
+
+    async fn covering(&self, block_number: u64) -> Result<Option<CommittedRange>> {
+        self.committed_range(block_number).await
+    }
+}
+
+/// Seconds per beacon chain slot.
+const SECONDS_PER_SLOT: u64 = 12;
+
+/// Beacon chain genesis time of the Ethereum networks the bridge is deployed on, by chain id.
+/// Other networks need `beacon_genesis_time` in the config.
+fn known_beacon_genesis_time(chain_id: u64) -> Option<u64> {
+    match chain_id {
+        1 => Some(1_606_824_023),
+        17_000 => Some(1_695_902_400),
+        11_155_111 => Some(1_655_733_600),
+        _ => None,
+    }
+}
+
+/// Ethereum head stored on Avail by the vector pallet. The pallet keeps the beacon slot and the
+/// execution state root of the head; its execution block is looked up on Ethereum by the slot's
+/// timestamp and checked against the state root.
+pub struct StoredEthHead {
+    sdk: SDK,
+    ethereum_url: Url,
+    beacon_genesis_time: Option<u64>,
+    /// Last head looked up on Ethereum, so the lookup runs once per head.
+    resolved: Mutex<Option<StoredSlot>>,
+}
+
+impl StoredEthHead {
+    pub fn new(sdk: SDK, ethereum_url: Url) -> Self {
+        StoredEthHead {
+            sdk,
+            ethereum_url,
+            beacon_genesis_time: None,
+            resolved: Mutex::new(None),
+        }
+    }
+
+    /// Sets the beacon chain genesis time used to find the execution block of a slot. `None`
+    /// uses the genesis time of mainnet, Sepolia or Holesky, depending on the chain id.
+    pub fn with_beacon_genesis_time(mut self, beacon_genesis_time: Option<u64>) -> Self {
+        self.beacon_genesis_time = beacon_genesis_time;
+        self
+    }
+
+    /// Latest head stored on Avail, with its execution block.
+    pub async fn latest(&self) -> Result<StoredSlot> {
+        let storage = self.sdk.api.storage().at_latest().await?;
+        let slot = storage
+            .fetch(&avail::storage().vector().head())
+            .await?
+            .ok_or(BridgeError::Avail("No Ethereum head stored".into()))?;
+        if let Some(stored) = *self.resolved.lock().unwrap_or_else(PoisonError::into_inner) {
+            if stored.slot == slot {
+                return Ok(stored);
+            }
+        }
+        let state_root = storage
+            .fetch(&avail::storage().vector().execution_state_roots(slot))
+            .await?
+            .ok_or(BridgeError::Avail(format!(
+                "No execution state root stored for slot {slot}"
+            )))?;
+        let provider = ProviderBuilder::new().on_http(self.ethereum_url.clone());
+        let genesis_time = match self.beacon_genesis_time {
+            Some(genesis_time) => genesis_time,
+            None => {
+                let chain_id = provider.get_chain_id().await?;
+                known_beacon_genesis_time(chain_id).ok_or(BridgeError::Config(format!(
+                    "Unknown beacon chain genesis time for chain {chain_id}, set beacon_genesis_time"
+                )))?
+            }
+        };
+        let timestamp = genesis_time + slot * SECONDS_PER_SLOT;
+        let stored = execution_block(&provider, slot, timestamp, state_root).await?;
+        *self.resolved.lock().unwrap_or_else(PoisonError::into_inner) = Some(stored);
+        Ok(stored)
+    }
+}
+
+impl Head for StoredEthHead {
+    type Covered = StoredSlot;
+
+    async fn covering(&self, block_number: u64) -> Result<Option<StoredSlot>> {
+        let stored = self.latest().await?;
+        Ok((stored.block_number >= block_number).then_some(stored))
+    }
+}
+
+/// Finds the execution block of the beacon `slot`, proposed at `timestamp`, and checks that its
+/// state root is `state_root`.
+async fn execution_block<T, P>(
+    provider: &P,
+    slot: u64,
+    timestamp: u64,
+    state_root: H256,
+) -> Result<StoredSlot>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let latest = get_block(provider, BlockNumberOrTag::Latest).await?;
+    let latest_number = latest
+        .header
+        .number
+        .ok_or(BridgeError::Decode("No block number!".into()))?;
+    // Every slot after the head's has at most one block, so the head's block is between this
+    // estimate and the latest block. Missed slots move it forward; binary search for the first
+    // block at or after the slot's timestamp.
+    let mut low = latest_number
+        .saturating_sub(latest.header.timestamp.saturating_sub(timestamp) / SECONDS_PER_SLOT);
+    let mut high = latest_number;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if get_block(provider, mid.into()).await?.header.timestamp < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    let block = get_block(provider, low.into()).await?;
+    if block.header.timestamp != timestamp || block.header.state_root.0 != state_root.0 {
+        return Err(BridgeError::Decode(format!(
+            "No Ethereum block with the state root stored for slot {slot}"
+        )));
+    }
+    let block_hash = block
+        .header
+        .hash
+        .ok_or(BridgeError::Decode("No block hash!".into()))?;
+    Ok(StoredSlot {
+        slot,
+        block_hash: H256(block_hash.0),
+        block_number: low,
+    })
+}
+
+async fn get_block<T, P>(provider: &P, number: BlockNumberOrTag) -> Result<Block>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    provider
+        .get_block_by_number(number, false)
+        .await?
+        .ok_or(BridgeError::Decode(format!("No Ethereum block {number:?}")))
+}
+
+/// Signals that a head may have moved, fed by a websocket subscription. The subscription is
+/// closed when this is dropped.
+pub struct HeadNotifications {
+    receiver: mpsc::Receiver<()>,
+    task: JoinHandle<()>,
+}
+
+impl HeadNotifications {
+    /// Subscribes to VectorX `HeadUpdate` logs over an Ethereum websocket.
+    pub async fn vectorx_head_updates(ethereum_ws_url: &str, vectorx: Address) -> Result<Self> {
+        let provider = ProviderBuilder::new()
+            .on_ws(WsConnect::new(ethereum_ws_url))
+            .await?;
+        let filter = Filter::new()
+            .address(vectorx)
+            .event_signature(VectorX::HeadUpdate::SIGNATURE_HASH);
+        let mut subscription = provider.subscribe_logs(&filter).await?;
+        let (sender, receiver) = mpsc::channel(1);
+        let task = tokio::spawn(async move {
+            // The provider owns the websocket, so it has to live as long as the subscription.
+            let _provider = provider;
+            loop {
+                match subscription.recv().await {
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+                // A full channel already holds an update the watcher has not seen yet.
+                let _ = sender.try_send(());
+            }
+        });
+        Ok(HeadNotifications { receiver, task })
+    }
+
+    /// Subscribes to finalized Avail blocks and signals those with a vector `HeadUpdated` event.
+    pub async fn avail_head_updates(sdk: &SDK) -> Result<Self> {
+        let mut blocks = sdk.api.blocks().subscribe_finalized().await?;
+        let (sender, receiver) = mpsc::channel(1);
+        let task = tokio::spawn(async move {
+            while let Some(Ok(block)) = blocks.next().await {
+                let Ok(events) = block.events().await else {
+                    break;
+                };
+                if let Ok(true) = events.has::<avail::vector::events::HeadUpdated>() {
+                    let _ = sender.try_send(());
+                }
+            }
+        });
+        Ok(HeadNotifications { receiver, task })
+    }
+}
+
+impl Drop for HeadNotifications {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Waits for a head to cover a block. With notifications the head is checked whenever it
+/// reports an update, and at least every `max_interval` in case an update was missed. Without
+/// them, or once the subscription ends, the head is polled every `MIN_POLL_INTERVAL`, doubling
+/// up to `max_interval`.
+pub struct HeadWatcher<H> {
+    head: H,
+    max_interval: Duration,
+    notifications: Option<HeadNotifications>,
+}
+
+impl<H: Head> HeadWatcher<H> {
+    pub fn new(head: H, max_interval: Duration) -> Self {
+        HeadWatcher {
+            head,
+            max_interval,
+            notifications: None,
+        }
+    }
+
+    /// Uses `notifications` to wake up on head updates. `None` keeps polling, so a failed
+    /// subscription can be passed straight through with `.ok()`.
+    pub fn with_notifications(mut self, notifications: Option<HeadNotifications>) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    pub fn head(&self) -> &H {
+        &self.head
+    }
+
+    /// Waits until the head covers `block_number` and returns what covers it.
+    pub async fn wait_until_covers(&mut self, block_number: u64) -> Result<H::Covered> {
+        let mut interval = MIN_POLL_INTERVAL.min(self.max_interval);
+        loop {
+            if let Some(covered) = self.head.covering(block_number).await? {
+                return Ok(covered);
+            }
+            if let Some(notifications) = &mut self.notifications {
+                let closed = tokio::select! {
+                    update = notifications.receiver.recv() => update.is_none(),
+                    _ = tokio::time::sleep(self.max_interval) => false,
+                };
+                if closed {
+                    self.notifications = None;
+                }
+                continue;
+            }
+            tokio::time::sleep(interval).await;
+            interval = (interval * 2).min(self.max_interval);
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use crate::avail_to_eth::{
+    find_inclusion, AvailToEthKind, AvailToEthStage, AvailToEthTransfer, ClaimOutcome, FinalizedTx,
+    IncludedTx,
+};
+use crate::eth_to_avail::{EthToAvailStage, EthToAvailTransfer};
+use crate::{connect_avail, BridgeError, Config, Result};
+use alloy::primitives::B256;
+use alloy_signer_local::PrivateKeySigner;
+use avail_rust::{Keypair, SDK};
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "direction", rename_all = "snake_case")]
+pub enum JournaledTransfer {
+    AvailToEth {
+        kind: AvailToEthKind,
+        stage: AvailToEthStage,
+        /// Whether the transfer is claimed (or data attested) with a transaction, rather than
+        /// only verified with a view call.
+        on_chain: bool,
+        avail_tx_hash: Option<H256>,
+        /// Latest finalized Avail block when the extrinsic was submitted, from which its
+        /// inclusion is searched if the process stopped before it was known.
+        #[serde(default)]
+        submitted_at: Option<u32>,
+        block_hash: Option<H256>,
+        extrinsic_index: Option<u32>,
+        message_id: Option<u64>,
+        claim_tx_hash: Option<B256>,
+    },
+    EthToAvail {
+        stage: EthToAvailStage,
+        eth_tx_hash: B256,
+        /// Unknown until the transaction is mined.
+        block_number: Option<u64>,
+        message_id: Option<u64>,
+        /// Unknown if the message was executed by another account.
+        executed_block_hash: Option<H256>,
+    },
+}
+
+impl JournaledTransfer {
+    pub fn is_complete(&self) -> bool {
+        match self {
+            JournaledTransfer::AvailToEth {
+                stage, on_chain, ..
+            } => {
+                *stage == AvailToEthStage::Claimed
+                    || (!*on_chain && *stage == AvailToEthStage::Verified)
+            }
+            JournaledTransfer::EthToAvail { stage, .. } => *stage == EthToAvailStage::Executed,
+        }
+    }
+
+    fn from_avail_to_eth(transfer: &AvailToEthTransfer, on_chain: bool) -> Self {
+        let included = transfer.included_tx();
+        JournaledTransfer::AvailToEth {
+            kind: transfer.kind(),
+            stage: transfer.stage(),
+            on_chain,
+            avail_tx_hash: transfer.tx_hash(),
+            submitted_at: transfer.submitted_at(),
+            block_hash: included.map(|i| i.block_hash),
+            extrinsic_index: included.map(|i| i.extrinsic_index),
+            message_id: transfer.message_id(),
+            claim_tx_hash: match transfer.claim_outcome() {
+                Some(ClaimOutcome::Claimed(receipt)) => Some(receipt.transaction_hash),
+                _ => None,
+            },
+        }
+    }
+
+    fn from_eth_to_avail(transfer: &EthToAvailTransfer) -> Self {
+        JournaledTransfer::EthToAvail {
+            stage: transfer.stage(),
+            eth_tx_hash: transfer.sent().tx_hash,
+            block_number: Some(transfer.sent().block_number),
+            message_id: Some(transfer.message_id()),
+            executed_block_hash: transfer.executed_block_hash(),
+        }
+    }
+
+    /// Merges fields that a resumed handle does not know about, e.g. the original extrinsic hash.
+    fn merge(self, previous: &JournaledTransfer) -> Self {
+        match (self, previous) {
+            (
+                JournaledTransfer::AvailToEth {
+                    kind,
+                    stage,
+                    on_chain,
+                    avail_tx_hash,
+                    submitted_at,
+                    block_hash,
+                    extrinsic_index,
+                    message_id,
+                    claim_tx_hash,
+                },
+                JournaledTransfer::AvailToEth {
+                    avail_tx_hash: previous_tx_hash,
+                    submitted_at: previous_submitted_at,
+                    message_id: previous_message_id,
+                    claim_tx_hash: previous_claim_tx_hash,
+                    ..
+                },
+            ) => JournaledTransfer::AvailToEth {
+                kind,
+                stage,
+                on_chain,
+                avail_tx_hash: avail_tx_hash.or(*previous_tx_hash),
+                submitted_at: submitted_at.or(*previous_submitted_at),
+                block_hash,
+                extrinsic_index,
+                message_id: message_id.or(*previous_message_id),
+                claim_tx_hash: claim_tx_hash.or(*previous_claim_tx_hash),
+            },
+            (transfer, _) => transfer,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub last_error: Option<String>,
+    #[serde(flatten)]
+    pub transfer: JournaledTransfer,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct JournalFile {
+    next_id: u64,
+    entries: Vec<JournalEntry>,
+    /// Last block scanned by each relayer, keyed by relayer name.
+    #[serde(default)]
+    cursors: BTreeMap<String, u64>,
+}
+
+/// Transfer journal stored as a JSON file. Every update rewrites the file atomically, so the
+/// journal survives the process dying at any point. It is meant to be used by one process at a
+/// time.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: JournalFile,
+}
+
+#[derive(Debug, Default)]
+pub struct ResumeReport {
+    pub completed: Vec<u64>,
+    pub failed: Vec<(u64, String)>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Journal {
+    /// Opens the journal at `path`, starting an empty one if the file does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            serde_json::from_str(&content).map_err(|e| {
+                BridgeError::Decode(format!("Cannot parse journal {}: {e}", path.display()))
+            })?
+        } else {
+            JournalFile::default()
+        };
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.file.entries
+    }
+
+    pub fn get(&self, id: u64) -> Option<&JournalEntry> {
+        self.file.entries.iter().find(|e| e.id == id)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.file
+            .entries
+            .iter()
+            .filter(|e| !e.transfer.is_complete())
+    }
+
+    /// Finds the Avail -> Ethereum transfer of the extrinsic at `extrinsic_index` in `block_hash`.
+    pub fn find_avail_to_eth(
+        &self,
+        block_hash: H256,
+        extrinsic_index: u32,
+    ) -> Option<&JournalEntry> {
+        self.file.entries.iter().find(|e| {
+            matches!(
+                e.transfer,
+                JournaledTransfer::AvailToEth {
+                    block_hash: Some(hash),
+                    extrinsic_index: Some(index),
+                    ..
+                } if hash == block_hash && index == extrinsic_index
+            )
+        })
+    }
+
+    /// Finds the Avail -> Ethereum transfer of the message `message_id` sent by the extrinsic at
+    /// `extrinsic_index` in `block_hash`. Entries recorded before their message id was known
+    /// only match on the extrinsic.
+    pub fn find_avail_to_eth_message(
+        &self,
+        block_hash: H256,
+        extrinsic_index: u32,
+        message_id: u64,
+    ) -> Option<&JournalEntry> {
+        self.file.entries.iter().find(|e| {
+            matches!(
+                e.transfer,
+                JournaledTransfer::AvailToEth {
+                    block_hash: Some(hash),
+                    extrinsic_index: Some(index),
+                    message_id: id,
+                    ..
+                } if hash == block_hash
+                    && index == extrinsic_index
+                    && id.map_or(true, |id| id == message_id)
+            )
+        })
+    }
+
+    pub fn find_eth_to_avail(&self, message_id: u64) -> Option<&JournalEntry> {
+        self.file.entries.iter().find(|e| {
+            matches!(
+                e.transfer,
+                JournaledTransfer::EthToAvail { message_id: Some(id), .. } if id == message_id
+            )
+        })
+    }
+
+    /// Last block scanned by the relayer `name`.
+    pub fn cursor(&self, name: &str) -> Option<u64> {
+        self.file.cursors.get(name).copied()
+    }
+
+    pub fn set_cursor(&mut self, name: &str, block_number: u64) -> Result<()> {
+        self.file.cursors.insert(name.to_string(), block_number);
+        self.save()
+    }
+
+    /// Records a new Avail -> Ethereum transfer. With `on_chain` unset, the transfer is complete
+    /// once its proof is verified with a view call.
+    pub fn record_avail_to_eth(
+        &mut self,
+        transfer: &AvailToEthTransfer,
+        on_chain: bool,
+    ) -> Result<u64> {
+        self.insert(JournaledTransfer::from_avail_to_eth(transfer, on_chain))
+    }
+
+    /// Records an Avail -> Ethereum transfer that was found on chain, e.g. by the relayer,
+    /// rather than submitted by this process.
+    pub fn record_finalized_avail_to_eth(
+        &mut self,
+        kind: AvailToEthKind,
+        block_hash: H256,
+        extrinsic_index: u32,
+        message_id: Option<u64>,
+        on_chain: bool,
+    ) -> Result<u64> {
+        self.insert(JournaledTransfer::AvailToEth {
+            kind,
+            stage: AvailToEthStage::Finalized,
+            on_chain,
+            avail_tx_hash: None,
+            submitted_at: None,
+            block_hash: Some(block_hash),
+            extrinsic_index: Some(extrinsic_index),
+            message_id,
+            claim_tx_hash: None,
+        })
+    }
+
+    pub fn record_eth_to_avail(&mut self, transfer: &EthToAvailTransfer) -> Result<u64> {
+        self.insert(JournaledTransfer::from_eth_to_avail(transfer))
+    }
+
+    /// Records an Avail -> Ethereum transfer, or updates the entry already recorded for its
+    /// extrinsic, e.g. when claiming a transfer that is in the journal. The entry is claimed
+    /// on chain if either the existing entry or `on_chain` says so.
+    pub fn record_or_update_avail_to_eth(
+        &mut self,
+        transfer: &AvailToEthTransfer,
+        on_chain: bool,
+    ) -> Result<u64> {
+        let existing = transfer.included_tx().and_then(|included| {
+            self.find_avail_to_eth(included.block_hash, included.extrinsic_index)
+        });
+        let Some(entry) = existing else {
+            return self.record_avail_to_eth(transfer, on_chain);
+        };
+        let id = entry.id;
+        let on_chain = on_chain
+            || matches!(
+                entry.transfer,
+                JournaledTransfer::AvailToEth { on_chain: true, .. }
+            );
+        self.update(id, JournaledTransfer::from_avail_to_eth(transfer, on_chain))?;
+        Ok(id)
+    }
+
+    /// Records an Ethereum -> Avail transfer, or updates the entry already recorded for its
+    /// transaction or message.
+    pub fn record_or_update_eth_to_avail(&mut self, transfer: &EthToAvailTransfer) -> Result<u64> {
+        let sent = transfer.sent();
+        let existing = self.file.entries.iter().find(|e| {
+            matches!(
+                e.transfer,
+                JournaledTransfer::EthToAvail { eth_tx_hash, message_id, .. }
+                    if eth_tx_hash == sent.tx_hash || message_id == Some(sent.message.id)
+            )
+        });
+        let Some(entry) = existing else {
+            return self.record_eth_to_avail(transfer);
+        };
+        let id = entry.id;
+        self.update_eth_to_avail(id, transfer)?;
+        Ok(id)
+    }
+
+    /// Records an Ethereum -> Avail transfer as soon as its transaction is broadcast, before it
+    /// is mined.
+    pub fn record_sent_eth_to_avail(&mut self, eth_tx_hash: B256) -> Result<u64> {
+        self.insert(JournaledTransfer::EthToAvail {
+            stage: EthToAvailStage::Sent,
+            eth_tx_hash,
+            block_number: None,
+            message_id: None,
+            executed_block_hash: None,
+        })
+    }
+
+    pub fn update_avail_to_eth(&mut self, id: u64, transfer: &AvailToEthTransfer) -> Result<()> {
+        let on_chain = match self.get(id).map(|e| &e.transfer) {
+            Some(JournaledTransfer::AvailToEth { on_chain, .. }) => *on_chain,
+            _ => true,
+        };
+        self.update(id, JournaledTransfer::from_avail_to_eth(transfer, on_chain))
+    }
+
+    pub fn update_eth_to_avail(&mut self, id: u64, transfer: &EthToAvailTransfer) -> Result<()> {
+        self.update(id, JournaledTransfer::from_eth_to_avail(transfer))
+    }
+
+    /// Marks an Ethereum -> Avail transfer as executed by someone else, so it is not resumed.
+    pub fn mark_eth_to_avail_executed(&mut self, id: u64) -> Result<()> {
+        let entry = self.entry_mut(id)?;
+        if let JournaledTransfer::EthToAvail { stage, .. } = &mut entry.transfer {
+            *stage = EthToAvailStage::Executed;
+        }
+        entry.last_error = None;
+        entry.updated_at = now();
+        self.save()
+    }
+
+    pub fn record_error(&mut self, id: u64, error: &impl fmt::Display) -> Result<()> {
+        let entry = self.entry_mut(id)?;
+        entry.last_error = Some(format!("{error:#}"));
+        entry.updated_at = now();
+        self.save()
+    }
+
+    fn insert(&mut self, transfer: JournaledTransfer) -> Result<u64> {
+        let id = self.file.next_id;
+        self.file.next_id += 1;
+        let timestamp = now();
+        self.file.entries.push(JournalEntry {
+            id,
+            created_at: timestamp,
+            updated_at: timestamp,
+            last_error: None,
+            transfer,
+        });
+        self.save()?;
+        Ok(id)
+    }
+
+    fn update(&mut self, id: u64, transfer: JournaledTransfer) -> Result<()> {
+        let entry = self.entry_mut(id)?;
+        entry.transfer = transfer.merge(&entry.transfer);
+        entry.last_error = None;
+        entry.updated_at = now();
+        self.save()
+    }
+
+    fn entry_mut(&mut self, id: u64) -> Result<&mut JournalEntry> {
+        self.file
+            .entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or(BridgeError::Transfer(format!("No journal entry {id}")))
+    }
+
+    fn save(&self) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.file)?)?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// Drives an Avail -> Ethereum transfer until its block is finalized, recording every stage in
+/// the journal.
+pub async fn drive_avail_to_eth_finalization(
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut AvailToEthTransfer,
+) -> Result<FinalizedTx> {
+    transfer.included().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    let finalized = transfer.finalized().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    Ok(finalized)
+}
+
+/// Drives an Avail -> Ethereum transfer to completion, recording every stage in the journal.
+pub async fn drive_avail_to_eth(
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut AvailToEthTransfer,
+) -> Result<ClaimOutcome> {
+    drive_avail_to_eth_finalization(journal, id, transfer).await?;
+    transfer.committed().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    transfer.proof().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    let outcome = transfer.claim().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    Ok(outcome)
+}
+
+/// Drives an Avail -> Ethereum transfer up to verifying its proof with a view call, recording
+/// every stage in the journal.
+pub async fn drive_avail_to_eth_verification(
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut AvailToEthTransfer,
+) -> Result<bool> {
+    drive_avail_to_eth_finalization(journal, id, transfer).await?;
+    transfer.committed().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    transfer.proof().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    let verified = transfer.verify().await?;
+    journal.update_avail_to_eth(id, transfer)?;
+    Ok(verified)
+}
+
+/// Drives an Ethereum -> Avail transfer to completion, recording every stage in the journal.
+/// Returns the block of the execution, or `None` if another account executed the message.
+pub async fn drive_eth_to_avail(
+    journal: &mut Journal,
+    id: u64,
+    transfer: &mut EthToAvailTransfer,
+    avail_signer: &Keypair,
+) -> Result<Option<H256>> {
+    journal.update_eth_to_avail(id, transfer)?;
+    transfer.stored().await?;
+    journal.update_eth_to_avail(id, transfer)?;
+    transfer.proof().await?;
+    journal.update_eth_to_avail(id, transfer)?;
+    if transfer.execution_status().await?.is_executed() {
+        info!(parent: transfer.span(), "Message was executed by another account");
+        journal.mark_eth_to_avail_executed(id)?;
+        return Ok(None);
+    }
+    let block_hash = transfer.execute(avail_signer).await?;
+    journal.update_eth_to_avail(id, transfer)?;
+    Ok(Some(block_hash))
+}
+
+/// Picks up every pending transfer in the journal and drives it to completion. Failures are
+/// recorded on the entry and do not stop the remaining transfers. With `entry_timeout`, a
+/// transfer still running after it is left pending so it cannot hold up the others.
+pub async fn resume_pending(
+    journal: &mut Journal,
+    config: &Config,
+    avail_signer: &Keypair,
+    eth_signer: PrivateKeySigner,
+    entry_timeout: Option<Duration>,
+) -> Result<ResumeReport> {
+    let pending = journal.pending().cloned().collect::<Vec<_>>();
+    let sdk = connect_avail(&config.avail_rpc_url).await?;
+    let mut report = ResumeReport::default();
+    for entry in pending {
+        let id = entry.id;
+        let resumed = resume_entry(journal, config, &sdk, avail_signer, &eth_signer, entry);
+        let result = match entry_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, resumed)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(BridgeError::Transfer(format!(
+                        "Not completed within {timeout:?}, left pending"
+                    )))
+                }),
+            None => resumed.await,
+        };
+        match result {
+            Ok(()) => report.completed.push(id),
+            Err(e) => {
+                warn!(journal_id = id, error = %e, "Transfer failed");
+                journal.record_error(id, &e)?;
+                report.failed.push((id, format!("{e:#}")));
+            }
+        }
+    }
+    Ok(report)
+}
+
+async fn resume_entry(
+    journal: &mut Journal,
+    config: &Config,
+    sdk: &SDK,
+    avail_signer: &Keypair,
+    eth_signer: &PrivateKeySigner,
+    entry: JournalEntry,
+) -> Result<()> {
+    match entry.transfer {
+        JournaledTransfer::AvailToEth {
+            kind,
+            on_chain,
+            avail_tx_hash,
+            submitted_at,
+            block_hash,
+            extrinsic_index,
+            ..
+        } => {
+            let included = match (block_hash, extrinsic_index, avail_tx_hash, submitted_at) {
+                (Some(block_hash), Some(extrinsic_index), _, _) => IncludedTx {
+                    block_hash,
+                    extrinsic_index,
+                },
+                // The process stopped between submitting and the extrinsic's inclusion.
+                (_, _, Some(tx_hash), Some(submitted_at)) => {
+                    find_inclusion(sdk, tx_hash, submitted_at).await?
+                }
+                _ => {
+                    return Err(BridgeError::Transfer(format!(
+                        "Transaction {avail_tx_hash:?} was journaled without its submission block"
+                    )))
+                }
+            };
+            let mut transfer = AvailToEthTransfer::resume(
+                config,
+                sdk.clone(),
+                eth_signer.clone(),
+                kind,
+                included.block_hash,
+                included.extrinsic_index,
+            )?;
+            if on_chain {
+                drive_avail_to_eth(journal, entry.id, &mut transfer).await?;
+            } else {
+                drive_avail_to_eth_verification(journal, entry.id, &mut transfer).await?;
+            }
+        }
+        JournaledTransfer::EthToAvail { eth_tx_hash, .. } => {
+            let mut transfer = EthToAvailTransfer::sent_in(config, eth_tx_hash)
+                .await?
+                .with_sdk(sdk.clone());
+            drive_eth_to_avail(journal, entry.id, &mut transfer, avail_signer).await?;
+        }
+    }
+    Ok(())
+}
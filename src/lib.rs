@@ -1,23 +1,72 @@
+use crate::attestation::CertificateError;
+use crate::merkle::ProofVerificationError;
+use crate::mpt::MptProofError;
+use crate::vectorx::LogScan;
 use alloy::primitives::Address;
 use alloy::primitives::Bytes;
 use alloy::primitives::FixedBytes;
 use alloy::primitives::Uint;
-use alloy_sol_types::sol;
+use alloy::primitives::B256;
+use alloy_sol_types::{sol, SolInterface};
+use alloy_transport::TransportError;
 use avail_rust::avail::runtime_types::avail_core::data_proof::message::AddressedMessage;
 use avail_rust::avail::runtime_types::avail_core::data_proof::message::Message as AvailBridgeMessage;
 use avail_rust::avail::runtime_types::bounded_collections::bounded_vec::BoundedVec;
 use avail_rust::avail_core::data_proof::AddressedMessage as CoreAddressedMessage;
-use serde::Deserialize;
+use avail_rust::SDK;
+use reqwest::Url;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sp_core::H256;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
+
+pub mod attestation;
+pub mod avail_to_eth;
+pub mod data_proof;
+pub mod eth_to_avail;
+pub mod events;
+pub mod fee;
+pub mod head_watcher;
+pub mod journal;
+pub mod merkle;
+pub mod metrics;
+pub mod mpt;
+pub mod relayer;
+pub mod simulate;
+pub mod status;
+pub mod storage_proof;
+pub mod tokens;
+pub mod vectorx;
 
 pub const ABI_JSON: &[u8] = include_bytes!("availbridge.json");
 
+pub const AVAIL_DOMAIN: u32 = 1;
+pub const ETHEREUM_DOMAIN: u32 = 2;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
 sol!(
-    #[sol(rpc)]
+    #[sol(rpc, all_derives)]
     AvailBridgeContract,
     "src/availbridge.json"
 );
 
+/// Logs JSON lines to stderr, filtered with `RUST_LOG` (`info` if unset). Every event carries
+/// the fields of the spans it is in, e.g. the transfer's message id and block hash.
+pub fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let _ = tracing_subscriber::fmt()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
 pub fn convert_addressed_message(message: CoreAddressedMessage) -> AddressedMessage {
     let msg = match message.message {
         avail_rust::avail_core::data_proof::Message::ArbitraryMessage(data) => {
@@ -47,6 +96,14 @@ pub fn enc_amount_to_value(amount: u128) -> Vec<u8> {
     data
 }
 
+/// Encodes a fungible token transfer as `abi.encode(assetId, amount)`.
+pub fn enc_fungible_token(asset_id: H256, amount: u128) -> Vec<u8> {
+    let mut data = asset_id.0.to_vec();
+    data.extend(vec![0; 32 - 16]);
+    data.extend(amount.to_be_bytes());
+    data
+}
+
 pub fn eth_seed_to_address(seed: &str) -> Address {
     let ethereum_signer = seed
         .parse::<alloy_signer_local::PrivateKeySigner>()
@@ -60,7 +117,7 @@ pub fn address_to_h256(from: Address) -> H256 {
     H256(v.as_slice().try_into().unwrap())
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BridgeApiMerkleProof {
     pub blob_root: H256,
@@ -76,22 +133,17 @@ pub struct BridgeApiMerkleProof {
     pub message: Option<CoreAddressedMessage>,
     pub range_hash: H256,
 }
-impl TryFrom<BridgeApiMerkleProof> for AvailBridgeContract::Message {
-    type Error = &'static str;
-    fn try_from(value: BridgeApiMerkleProof) -> Result<Self, Self::Error> {
-        let Some(message) = value.message else {
-            return Err("Message not found");
-        };
+impl From<CoreAddressedMessage> for AvailBridgeContract::Message {
+    fn from(message: CoreAddressedMessage) -> Self {
         let (msg_type, data) = match message.message {
             avail_rust::avail_core::data_proof::Message::ArbitraryMessage(data) => {
                 (1u8, data.to_vec())
             }
-            avail_rust::avail_core::data_proof::Message::FungibleToken {
-                asset_id: _,
-                amount,
-            } => (2u8, enc_amount_to_value(amount)),
+            avail_rust::avail_core::data_proof::Message::FungibleToken { asset_id, amount } => {
+                (2u8, enc_fungible_token(asset_id, amount))
+            }
         };
-        Ok(Self {
+        Self {
             messageType: FixedBytes::from_slice(&[msg_type]),
             from: message.from.0.into(),
             to: message.to.0.into(),
@@ -99,7 +151,19 @@ impl TryFrom<BridgeApiMerkleProof> for AvailBridgeContract::Message {
             destinationDomain: message.destination_domain,
             data: Bytes::copy_from_slice(data.as_slice()),
             messageId: message.id,
-        })
+        }
+    }
+}
+
+impl TryFrom<BridgeApiMerkleProof> for AvailBridgeContract::Message {
+    type Error = BridgeError;
+    fn try_from(value: BridgeApiMerkleProof) -> Result<Self, Self::Error> {
+        let Some(message) = value.message else {
+            return Err(BridgeError::Decode(
+                "Proof does not contain a message".into(),
+            ));
+        };
+        Ok(message.into())
     }
 }
 
@@ -126,16 +190,493 @@ impl From<BridgeApiMerkleProof> for AvailBridgeContract::MerkleProofInput {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Deserialize, Clone)]
 pub struct Config {
     pub avail_rpc_url: String,
     pub avail_sender_mnemonic: String,
     pub ethereum_secret: String,
     pub bridge_api_url: String,
     pub ethereum_url: String,
+    /// Websocket endpoint used to wait for VectorX head updates instead of polling.
+    #[serde(default)]
+    pub ethereum_ws_url: Option<String>,
     pub contract_address: String,
     pub message_data: String,
     pub amount_to_send: u64,
     pub recipient: String,
     pub receive_message_contract_address: String,
+    #[serde(default)]
+    pub fee_margin_percent: u64,
+    #[serde(default)]
+    pub proof_source: ProofSource,
+    /// Storage slot of the bridge's `isSent` mapping, used with `ProofSource::Rpc`.
+    #[serde(default = "default_message_mapping_slot")]
+    pub message_mapping_slot: u64,
+    /// Address the relayers serve Prometheus metrics on, e.g. `127.0.0.1:9090`.
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+    /// Unix time of the beacon chain genesis, used to find the Ethereum block of the slot stored
+    /// on Avail. Known for mainnet, Sepolia and Holesky.
+    #[serde(default)]
+    pub beacon_genesis_time: Option<u64>,
+    /// Ethereum blocks queried per `eth_getLogs` request, halved while the provider rejects it.
+    #[serde(default = "default_log_chunk_size")]
+    pub log_chunk_size: u64,
+    /// How many Ethereum blocks back VectorX commitments are searched.
+    #[serde(default = "default_max_log_lookback")]
+    pub max_log_lookback: u64,
+}
+
+fn default_message_mapping_slot() -> u64 {
+    storage_proof::MESSAGE_MAPPING_SLOT
+}
+
+fn default_log_chunk_size() -> u64 {
+    vectorx::DEFAULT_LOG_CHUNK_SIZE
+}
+
+fn default_max_log_lookback() -> u64 {
+    vectorx::DEFAULT_MAX_LOG_LOOKBACK
+}
+
+/// Where proofs are built from.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSource {
+    /// Trust the proofs served by the Bridge API.
+    #[default]
+    BridgeApi,
+    /// Build proofs from the chains' JSON-RPC endpoints.
+    Rpc,
+}
+
+/// Leaves out the mnemonic and the private key, so the config can be logged.
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("avail_rpc_url", &self.avail_rpc_url)
+            .field("avail_sender_mnemonic", &"<redacted>")
+            .field("ethereum_secret", &"<redacted>")
+            .field("bridge_api_url", &self.bridge_api_url)
+            .field("ethereum_url", &self.ethereum_url)
+            .field("ethereum_ws_url", &self.ethereum_ws_url)
+            .field("contract_address", &self.contract_address)
+            .field("message_data", &self.message_data)
+            .field("amount_to_send", &self.amount_to_send)
+            .field("recipient", &self.recipient)
+            .field(
+                "receive_message_contract_address",
+                &self.receive_message_contract_address,
+            )
+            .field("fee_margin_percent", &self.fee_margin_percent)
+            .field("proof_source", &self.proof_source)
+            .field("message_mapping_slot", &self.message_mapping_slot)
+            .field("metrics_address", &self.metrics_address)
+            .field("beacon_genesis_time", &self.beacon_genesis_time)
+            .field("log_chunk_size", &self.log_chunk_size)
+            .field("max_log_lookback", &self.max_log_lookback)
+            .finish()
+    }
+}
+
+impl Config {
+    pub fn parse_ethereum_url(&self) -> Result<Url> {
+        Url::parse(self.ethereum_url.as_str())
+            .map_err(|e| BridgeError::Config(format!("Invalid ethereum_url: {e}")))
+    }
+
+    pub fn parse_contract_address(&self) -> Result<Address> {
+        self.contract_address
+            .parse()
+            .map_err(|e| BridgeError::Config(format!("Invalid contract_address: {e}")))
+    }
+
+    pub fn parse_metrics_address(&self) -> Result<Option<SocketAddr>> {
+        self.metrics_address
+            .as_deref()
+            .map(|address| {
+                address
+                    .parse()
+                    .map_err(|e| BridgeError::Config(format!("Invalid metrics_address: {e}")))
+            })
+            .transpose()
+    }
+
+    pub fn log_scan(&self) -> LogScan {
+        LogScan {
+            chunk_size: self.log_chunk_size,
+            max_lookback: self.max_log_lookback,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AvailHeadInfo {
+    pub data: AvailHeadData,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AvailHeadData {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl AvailHeadData {
+    pub fn contains(&self, block_num: u64) -> bool {
+        (self.start..=self.end).contains(&block_num)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EthereumSlotInfo {
+    pub slot: u64,
+    pub timestamp: u64,
+    pub timestamp_diff: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockInfo {
+    pub block_number: u64,
+    pub block_hash: H256,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStorageProof {
+    #[serde(serialize_with = "bytes_to_hex", deserialize_with = "bytes_from_hex")]
+    pub account_proof: Vec<Vec<u8>>,
+    #[serde(serialize_with = "bytes_to_hex", deserialize_with = "bytes_from_hex")]
+    pub storage_proof: Vec<Vec<u8>>,
+}
+
+fn bytes_to_hex<S>(value: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_seq(value.iter().map(|e| format!("0x{}", hex::encode(e))))
+}
+
+fn bytes_from_hex<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let buf = <Vec<String>>::deserialize(deserializer)?;
+    buf.iter()
+        .map(|e| {
+            let without_prefix = e.trim_start_matches("0x");
+            hex::decode(without_prefix).map_err(serde::de::Error::custom)
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub enum BridgeApiError {
+    Request(reqwest::Error),
+    Status {
+        url: String,
+        status: reqwest::StatusCode,
+        body: String,
+    },
+    Decode {
+        url: String,
+        body: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for BridgeApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeApiError::Request(e) => write!(f, "Bridge API request failed: {e}"),
+            BridgeApiError::Status { url, status, body } => {
+                write!(f, "Bridge API returned {status} for {url}: {body}")
+            }
+            BridgeApiError::Decode { url, source, .. } => {
+                write!(f, "Cannot decode Bridge API response from {url}: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BridgeApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BridgeApiError::Request(e) => Some(e),
+            BridgeApiError::Status { .. } => None,
+            BridgeApiError::Decode { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<reqwest::Error> for BridgeApiError {
+    fn from(value: reqwest::Error) -> Self {
+        BridgeApiError::Request(value)
+    }
+}
+
+pub type Result<T, E = BridgeError> = std::result::Result<T, E>;
+
+/// Error returned by the library flows.
+#[derive(Debug)]
+pub enum BridgeError {
+    /// Invalid configuration, e.g. an unparsable URL, address or key.
+    Config(String),
+    /// Request to the Bridge API failed.
+    BridgeApi(BridgeApiError),
+    /// Data returned by Avail, Ethereum or the Bridge API cannot be decoded.
+    Decode(String),
+    /// Avail RPC request failed or the extrinsic failed to dispatch.
+    Avail(String),
+    /// Ethereum call reverted with one of the bridge contract's custom errors.
+    Revert(AvailBridgeContract::AvailBridgeContractErrors),
+    /// Ethereum RPC request failed, or a call reverted without a known custom error.
+    Ethereum(String),
+    /// Ethereum transaction was mined but reverted.
+    TransactionReverted(B256),
+    InvalidProof(ProofVerificationError),
+    InvalidCertificate(CertificateError),
+    /// Account or storage proof of an Ethereum -> Avail message is invalid.
+    InvalidStorageProof(MptProofError),
+    /// The transfer does not exist or does not match what was sent.
+    Transfer(String),
+    Io(std::io::Error),
+}
+
+/// Connects to the Avail node at `url`.
+pub async fn connect_avail(url: &str) -> Result<SDK> {
+    SDK::new(url)
+        .await
+        .map_err(|e| BridgeError::Avail(format!("Cannot initialize SDK: {e}")))
+}
+
+impl BridgeError {
+    pub fn avail(e: impl fmt::Display) -> Self {
+        BridgeError::Avail(e.to_string())
+    }
+
+    pub fn ethereum(e: impl fmt::Display) -> Self {
+        BridgeError::Ethereum(e.to_string())
+    }
+
+    /// The bridge contract's custom error, if an Ethereum call reverted with one.
+    pub fn contract_error(&self) -> Option<&AvailBridgeContract::AvailBridgeContractErrors> {
+        match self {
+            BridgeError::Revert(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn is_already_bridged(&self) -> bool {
+        matches!(
+            self.contract_error(),
+            Some(AvailBridgeContract::AvailBridgeContractErrors::AlreadyBridged(_))
+        )
+    }
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::Config(e) => write!(f, "Invalid config: {e}"),
+            BridgeError::BridgeApi(e) => write!(f, "{e}"),
+            BridgeError::Decode(e) => write!(f, "Decode error: {e}"),
+            BridgeError::Avail(e) => write!(f, "Avail error: {e}"),
+            BridgeError::Revert(e) => write!(f, "Bridge contract reverted: {e:?}"),
+            BridgeError::Ethereum(e) => write!(f, "Ethereum error: {e}"),
+            BridgeError::TransactionReverted(tx_hash) => {
+                write!(f, "Transaction {tx_hash} reverted")
+            }
+            BridgeError::InvalidProof(e) => write!(f, "{e}"),
+            BridgeError::InvalidCertificate(e) => write!(f, "Invalid certificate: {e}"),
+            BridgeError::InvalidStorageProof(e) => write!(f, "Invalid storage proof: {e}"),
+            BridgeError::Transfer(e) => write!(f, "{e}"),
+            BridgeError::Io(e) => write!(f, "IO error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BridgeError::BridgeApi(e) => Some(e),
+            BridgeError::InvalidProof(e) => Some(e),
+            BridgeError::InvalidCertificate(e) => Some(e),
+            BridgeError::InvalidStorageProof(e) => Some(e),
+            BridgeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<BridgeApiError> for BridgeError {
+    fn from(value: BridgeApiError) -> Self {
+        BridgeError::BridgeApi(value)
+    }
+}
+
+impl From<ProofVerificationError> for BridgeError {
+    fn from(value: ProofVerificationError) -> Self {
+        BridgeError::InvalidProof(value)
+    }
+}
+
+impl From<CertificateError> for BridgeError {
+    fn from(value: CertificateError) -> Self {
+        BridgeError::InvalidCertificate(value)
+    }
+}
+
+impl From<MptProofError> for BridgeError {
+    fn from(value: MptProofError) -> Self {
+        BridgeError::InvalidStorageProof(value)
+    }
+}
+
+impl From<alloy_sol_types::Error> for BridgeError {
+    fn from(value: alloy_sol_types::Error) -> Self {
+        BridgeError::Decode(value.to_string())
+    }
+}
+
+impl From<serde_json::Error> for BridgeError {
+    fn from(value: serde_json::Error) -> Self {
+        BridgeError::Decode(value.to_string())
+    }
+}
+
+impl From<std::io::Error> for BridgeError {
+    fn from(value: std::io::Error) -> Self {
+        BridgeError::Io(value)
+    }
+}
+
+impl From<avail_rust::subxt::Error> for BridgeError {
+    fn from(value: avail_rust::subxt::Error) -> Self {
+        BridgeError::Avail(value.to_string())
+    }
+}
+
+impl From<TransportError> for BridgeError {
+    fn from(value: TransportError) -> Self {
+        match decode_revert(&value) {
+            Some(e) => BridgeError::Revert(e),
+            None => BridgeError::Ethereum(value.to_string()),
+        }
+    }
+}
+
+impl From<alloy_contract::Error> for BridgeError {
+    fn from(value: alloy_contract::Error) -> Self {
+        match value {
+            alloy_contract::Error::TransportError(e) => e.into(),
+            e => BridgeError::Ethereum(e.to_string()),
+        }
+    }
+}
+
+/// Decodes the revert data of a failed `eth_call`/`eth_estimateGas` into the bridge contract's
+/// custom errors.
+pub fn decode_revert(
+    error: &TransportError,
+) -> Option<AvailBridgeContract::AvailBridgeContractErrors> {
+    let data = error.as_error_resp()?.data.as_ref()?;
+    let data = serde_json::from_str::<Bytes>(data.get()).ok()?;
+    AvailBridgeContract::AvailBridgeContractErrors::abi_decode(&data, true).ok()
+}
+
+#[derive(Debug, Clone)]
+pub struct BridgeApiClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl BridgeApiClient {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub fn new(base_url: &str) -> Result<Self, BridgeApiError> {
+        let client = reqwest::Client::builder()
+            .timeout(Self::DEFAULT_TIMEOUT)
+            .build()?;
+        Ok(Self::with_client(client, base_url))
+    }
+
+    pub fn with_client(client: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Avail block range currently committed by VectorX on Ethereum.
+    pub async fn avail_head(&self) -> Result<AvailHeadInfo, BridgeApiError> {
+        self.get("/avl/head").await
+    }
+
+    /// Latest Ethereum slot stored on Avail.
+    pub async fn eth_head(&self) -> Result<EthereumSlotInfo, BridgeApiError> {
+        self.get("/eth/head").await
+    }
+
+    pub async fn beacon_slot(&self, slot: u64) -> Result<BlockInfo, BridgeApiError> {
+        self.get(&format!("/beacon/slot/{slot}")).await
+    }
+
+    /// Merkle proof for an Avail extrinsic, used on Ethereum.
+    pub async fn eth_proof(
+        &self,
+        block_hash: H256,
+        extrinsic_index: u32,
+    ) -> Result<BridgeApiMerkleProof, BridgeApiError> {
+        self.get(&format!(
+            "/eth/proof/{block_hash:?}?index={extrinsic_index}"
+        ))
+        .await
+    }
+
+    /// Account and storage proofs for an Ethereum message, used on Avail.
+    pub async fn avl_proof(
+        &self,
+        block_hash: H256,
+        message_id: u64,
+    ) -> Result<AccountStorageProof, BridgeApiError> {
+        self.get(&format!("/avl/proof/{block_hash:?}/{message_id}"))
+            .await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, BridgeApiError> {
+        let result = self.fetch(path).await;
+        metrics::bridge_api_request(path, result.as_ref().map(|_| ()));
+        result
+    }
+
+    async fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<T, BridgeApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.client.get(&url).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(BridgeApiError::Status { url, status, body });
+        }
+        serde_json::from_str(&body).map_err(|source| BridgeApiError::Decode { url, body, source })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_debug_redacts_secrets() {
+        let config: Config = toml::from_str(include_str!("../config.toml")).unwrap();
+        let debug = format!("{config:?}");
+        assert!(!debug.contains(&config.avail_sender_mnemonic));
+        assert!(!debug.contains(&config.ethereum_secret));
+        assert!(debug.contains(&config.avail_rpc_url));
+    }
 }
@@ -0,0 +1,434 @@
+use crate::{AvailBridgeContract, BridgeApiMerkleProof};
+use alloy_sol_types::SolValue;
+use avail_rust::avail_core::data_proof::AddressedMessage as CoreAddressedMessage;
+use sp_core::hashing::sha2_256;
+use sp_core::{keccak_256, H256};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofVerificationError {
+    BlobRootEmpty,
+    BridgeRootEmpty,
+    DataRootCommitmentEmpty,
+    /// The message attached to the proof does not hash to `leaf`.
+    InvalidLeaf {
+        leaf: H256,
+        computed: H256,
+    },
+    /// `leaf` with `leaf_proof` at `leaf_index` does not hash to the blob or bridge root.
+    InvalidLeafProof {
+        root: H256,
+        computed: H256,
+    },
+    /// `blob_root` and `bridge_root` do not hash to `data_root`.
+    InvalidDataRoot {
+        data_root: H256,
+        computed: H256,
+    },
+    /// `data_root` with `data_root_proof` at `data_root_index` does not hash (with SHA-256) to
+    /// `data_root_commitment`.
+    InvalidDataRootProof {
+        commitment: H256,
+        computed: H256,
+    },
+}
+
+impl fmt::Display for ProofVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofVerificationError::BlobRootEmpty => write!(f, "Blob root is empty"),
+            ProofVerificationError::BridgeRootEmpty => write!(f, "Bridge root is empty"),
+            ProofVerificationError::DataRootCommitmentEmpty => {
+                write!(f, "Data root commitment is empty")
+            }
+            ProofVerificationError::InvalidLeaf { leaf, computed } => {
+                write!(
+                    f,
+                    "Invalid leaf: expected {leaf:?}, message hashes to {computed:?}"
+                )
+            }
+            ProofVerificationError::InvalidLeafProof { root, computed } => {
+                write!(
+                    f,
+                    "Invalid leaf proof: expected root {root:?}, got {computed:?}"
+                )
+            }
+            ProofVerificationError::InvalidDataRoot {
+                data_root,
+                computed,
+            } => write!(
+                f,
+                "Invalid data root: expected {data_root:?}, got {computed:?}"
+            ),
+            ProofVerificationError::InvalidDataRootProof {
+                commitment,
+                computed,
+            } => write!(
+                f,
+                "Invalid data root proof: expected commitment {commitment:?}, got {computed:?}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProofVerificationError {}
+
+/// Computes the bridge leaf of `message` as `keccak256(abi.encode(message))`, the same way the
+/// bridge contract does in `receiveMessage` and friends.
+pub fn message_leaf(message: &CoreAddressedMessage) -> H256 {
+    let message: AvailBridgeContract::Message = message.clone().into();
+    H256(keccak_256(&message.abi_encode()))
+}
+
+/// Computes the blob leaf of data submitted with `submit_data`.
+pub fn blob_leaf(data: &[u8]) -> H256 {
+    H256(keccak_256(data))
+}
+
+/// Hashes `leaf` up the tree the same way the bridge contract's `Merkle.verify` does and returns
+/// the resulting root, or `None` if `index` does not fit in a tree of the proof's depth.
+pub fn compute_merkle_root(proof: &[H256], index: u64, leaf: H256) -> Option<H256> {
+    compute_root_with(proof, index, leaf, keccak_256)
+}
+
+/// Same as `compute_merkle_root` with SHA-256, as in `Merkle.verifySha2`. VectorX data root
+/// commitments are SHA-256 trees.
+pub fn compute_sha2_merkle_root(proof: &[H256], index: u64, leaf: H256) -> Option<H256> {
+    compute_root_with(proof, index, leaf, sha2_256)
+}
+
+fn compute_root_with(
+    proof: &[H256],
+    index: u64,
+    leaf: H256,
+    hash: fn(&[u8]) -> [u8; 32],
+) -> Option<H256> {
+    let mut index = index;
+    let mut node = leaf;
+    for sibling in proof {
+        node = hash_pair(node, *sibling, index & 1 == 1, hash);
+        index >>= 1;
+    }
+    (index == 0).then_some(node)
+}
+
+fn hash_pair(node: H256, sibling: H256, is_right: bool, hash: fn(&[u8]) -> [u8; 32]) -> H256 {
+    let mut buf = [0u8; 64];
+    if is_right {
+        buf[..32].copy_from_slice(sibling.as_bytes());
+        buf[32..].copy_from_slice(node.as_bytes());
+    } else {
+        buf[..32].copy_from_slice(node.as_bytes());
+        buf[32..].copy_from_slice(sibling.as_bytes());
+    }
+    H256(hash(&buf))
+}
+
+/// Builds the SHA-256 tree over `leaves`, padded with zero leaves to the next power of two, and
+/// returns its root together with the proof for the leaf at `index`.
+pub fn sha2_merkle_proof(leaves: &[H256], index: usize) -> Option<(H256, Vec<H256>)> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut layer = leaves.to_vec();
+    layer.resize(leaves.len().next_power_of_two(), H256::zero());
+    let mut index = index;
+    let mut proof = Vec::new();
+    while layer.len() > 1 {
+        proof.push(layer[index ^ 1]);
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair[1], false, sha2_256))
+            .collect();
+        index >>= 1;
+    }
+    Some((layer[0], proof))
+}
+
+pub fn verify_merkle_proof(proof: &[H256], root: H256, index: u64, leaf: H256) -> bool {
+    compute_merkle_root(proof, index, leaf) == Some(root)
+}
+
+impl BridgeApiMerkleProof {
+    /// Verifies the proof locally, checking the bridge leaf if the proof carries a message and
+    /// the blob leaf otherwise.
+    pub fn verify(&self) -> Result<(), ProofVerificationError> {
+        if self.message.is_some() {
+            self.verify_bridge_leaf()
+        } else {
+            self.verify_blob_leaf()
+        }
+    }
+
+    /// Returns true if the proof's leaf is the leaf of `message`.
+    pub fn is_for_message(&self, message: &CoreAddressedMessage) -> bool {
+        self.leaf == message_leaf(message)
+    }
+
+    /// Returns true if the proof's leaf is the blob leaf of `data`.
+    pub fn is_for_data(&self, data: &[u8]) -> bool {
+        self.leaf == blob_leaf(data)
+    }
+
+    /// Mirrors the contract's `verifyBridgeLeaf`: `leaf` is the hash of the encoded message.
+    pub fn verify_bridge_leaf(&self) -> Result<(), ProofVerificationError> {
+        if self.bridge_root.is_zero() {
+            return Err(ProofVerificationError::BridgeRootEmpty);
+        }
+        if let Some(message) = &self.message {
+            let computed = message_leaf(message);
+            if computed != self.leaf {
+                return Err(ProofVerificationError::InvalidLeaf {
+                    leaf: self.leaf,
+                    computed,
+                });
+            }
+        }
+        self.verify_leaf(self.leaf, self.bridge_root)?;
+        self.verify_data_root()
+    }
+
+    /// Mirrors the contract's `verifyBlobLeaf`: `leaf` is the hash of the submitted data and is
+    /// hashed once more before being checked against the blob root.
+    pub fn verify_blob_leaf(&self) -> Result<(), ProofVerificationError> {
+        if self.blob_root.is_zero() {
+            return Err(ProofVerificationError::BlobRootEmpty);
+        }
+        self.verify_leaf(H256(keccak_256(self.leaf.as_bytes())), self.blob_root)?;
+        self.verify_data_root()
+    }
+
+    fn verify_leaf(&self, leaf: H256, root: H256) -> Result<(), ProofVerificationError> {
+        let computed =
+            compute_merkle_root(&self.leaf_proof, self.leaf_index as u64, leaf).unwrap_or_default();
+        if computed != root {
+            return Err(ProofVerificationError::InvalidLeafProof { root, computed });
+        }
+        Ok(())
+    }
+
+    fn verify_data_root(&self) -> Result<(), ProofVerificationError> {
+        let mut buf = [0u8; 64];
+        buf[..32].copy_from_slice(self.blob_root.as_bytes());
+        buf[32..].copy_from_slice(self.bridge_root.as_bytes());
+        let computed = H256(keccak_256(&buf));
+        if computed != self.data_root {
+            return Err(ProofVerificationError::InvalidDataRoot {
+                data_root: self.data_root,
+                computed,
+            });
+        }
+
+        if self.data_root_commitment.is_zero() {
+            return Err(ProofVerificationError::DataRootCommitmentEmpty);
+        }
+        let computed = compute_sha2_merkle_root(
+            &self.data_root_proof,
+            self.data_root_index as u64,
+            self.data_root,
+        )
+        .unwrap_or_default();
+        if computed != self.data_root_commitment {
+            return Err(ProofVerificationError::InvalidDataRootProof {
+                commitment: self.data_root_commitment,
+                computed,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use avail_rust::avail_core::data_proof::Message;
+    use hex_literal::hex;
+
+    const LEAVES: [H256; 4] = [
+        H256::repeat_byte(1),
+        H256::repeat_byte(2),
+        H256::repeat_byte(3),
+        H256::repeat_byte(4),
+    ];
+
+    fn blob_proof() -> BridgeApiMerkleProof {
+        serde_json::from_str(include_str!("../tests/fixtures/blob_proof.json")).unwrap()
+    }
+
+    fn message() -> CoreAddressedMessage {
+        CoreAddressedMessage {
+            message: Message::ArbitraryMessage(b"hello".to_vec().try_into().unwrap()),
+            from: H256::repeat_byte(0xaa),
+            to: H256::repeat_byte(0xbb),
+            origin_domain: 1,
+            destination_domain: 2,
+            id: 7,
+        }
+    }
+
+    #[test]
+    fn keccak_root_matches_known_vector() {
+        let proof = [
+            LEAVES[3],
+            H256(hex!(
+                "346d8c96a2454213fcc0daff3c96ad0398148181b9fa6488f7ae2c0af5b20aa0"
+            )),
+        ];
+        assert_eq!(
+            compute_merkle_root(&proof, 2, LEAVES[2]),
+            Some(H256(hex!(
+                "99976d3b1539e7cfaca77649ac7536fec61db00fb0835634915b4d542fff06ae"
+            )))
+        );
+    }
+
+    #[test]
+    fn sha2_root_matches_known_vector() {
+        let root = H256(hex!(
+            "2c0c4083be2badf7c9f9046d8730d21e034c1ce50f519c166d7605848b17b0d5"
+        ));
+        let proof = vec![
+            LEAVES[3],
+            H256(hex!(
+                "f818afd37a6dc3bc92fb44731011277006db4efa6e9023cd7468c02335d22a4d"
+            )),
+        ];
+        assert_eq!(compute_sha2_merkle_root(&proof, 2, LEAVES[2]), Some(root));
+        assert_eq!(sha2_merkle_proof(&LEAVES, 2), Some((root, proof)));
+    }
+
+    #[test]
+    fn index_past_proof_depth_has_no_root() {
+        let proof = [LEAVES[1], LEAVES[2]];
+        assert_eq!(compute_merkle_root(&proof, 4, LEAVES[0]), None);
+        assert_eq!(compute_sha2_merkle_root(&proof, 4, LEAVES[0]), None);
+        assert_eq!(sha2_merkle_proof(&LEAVES, 4), None);
+    }
+
+    /// Leaves computed independently as `keccak256(abi.encode(message))` of `IAvailBridge.Message`.
+    #[test]
+    fn arbitrary_message_leaf_matches_contract_encoding() {
+        assert_eq!(
+            message_leaf(&message()),
+            H256(hex!(
+                "d48d2ec371065200bd69c09f62f8264c3b68c080c971c3f4d907e5f24c1547af"
+            ))
+        );
+    }
+
+    #[test]
+    fn fungible_token_message_leaf_matches_contract_encoding() {
+        let message = CoreAddressedMessage {
+            message: Message::FungibleToken {
+                asset_id: H256::zero(),
+                amount: 1_000_000_000_000_000_000,
+            },
+            from: H256::repeat_byte(0xcc),
+            to: H256::repeat_byte(0xdd),
+            origin_domain: 1,
+            destination_domain: 2,
+            id: (42 << 32) | 3,
+        };
+        assert_eq!(
+            message_leaf(&message),
+            H256(hex!(
+                "ae075d67b3c839af887be03e660d8cd730b1dc92f8ab602ff2b21cb7c53d3c72"
+            ))
+        );
+    }
+
+    #[test]
+    fn blob_leaf_matches_known_vector() {
+        assert_eq!(blob_leaf(b"Hello, Avail bridge!"), blob_proof().leaf);
+        assert_eq!(
+            blob_leaf(b""),
+            H256(hex!(
+                "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+            ))
+        );
+    }
+
+    #[test]
+    fn bridge_api_proof_verifies() {
+        assert_eq!(blob_proof().verify(), Ok(()));
+    }
+
+    #[test]
+    fn empty_blob_root_is_rejected() {
+        let mut proof = blob_proof();
+        proof.blob_root = H256::zero();
+        assert_eq!(proof.verify(), Err(ProofVerificationError::BlobRootEmpty));
+    }
+
+    #[test]
+    fn empty_bridge_root_is_rejected() {
+        let mut proof = blob_proof();
+        proof.message = Some(message());
+        proof.bridge_root = H256::zero();
+        assert_eq!(proof.verify(), Err(ProofVerificationError::BridgeRootEmpty));
+    }
+
+    #[test]
+    fn empty_data_root_commitment_is_rejected() {
+        let mut proof = blob_proof();
+        proof.data_root_commitment = H256::zero();
+        assert_eq!(
+            proof.verify(),
+            Err(ProofVerificationError::DataRootCommitmentEmpty)
+        );
+    }
+
+    #[test]
+    fn message_not_hashing_to_leaf_is_rejected() {
+        let mut proof = blob_proof();
+        proof.message = Some(message());
+        assert_eq!(
+            proof.verify(),
+            Err(ProofVerificationError::InvalidLeaf {
+                leaf: proof.leaf,
+                computed: message_leaf(&message()),
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_leaf_proof_is_rejected() {
+        let mut proof = blob_proof();
+        proof.leaf_proof[0].0[0] ^= 1;
+        assert!(matches!(
+            proof.verify(),
+            Err(ProofVerificationError::InvalidLeafProof { root, .. }) if root == proof.blob_root
+        ));
+    }
+
+    #[test]
+    fn tampered_data_root_is_rejected() {
+        let mut proof = blob_proof();
+        proof.data_root.0[0] ^= 1;
+        assert!(matches!(
+            proof.verify(),
+            Err(ProofVerificationError::InvalidDataRoot { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_data_root_proof_is_rejected() {
+        let mut proof = blob_proof();
+        proof.data_root_proof[1].0[0] ^= 1;
+        assert!(matches!(
+            proof.verify(),
+            Err(ProofVerificationError::InvalidDataRootProof { commitment, .. })
+                if commitment == proof.data_root_commitment
+        ));
+    }
+
+    #[test]
+    fn data_root_index_past_proof_depth_is_rejected() {
+        let mut proof = blob_proof();
+        proof.data_root_index = 21;
+        assert!(matches!(
+            proof.verify(),
+            Err(ProofVerificationError::InvalidDataRootProof { .. })
+        ));
+    }
+}
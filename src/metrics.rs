@@ -0,0 +1,412 @@
+//! Prometheus metrics for bridge operations. They are only collected when the crate is built
+//! with the `metrics` feature; without it every function here is a no-op and `serve` fails, so
+//! callers need no `cfg` of their own.
+
+use crate::avail_to_eth::AvailToEthStage;
+use crate::eth_to_avail::EthToAvailStage;
+use crate::BridgeError;
+
+pub use imp::*;
+
+impl AvailToEthStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AvailToEthStage::Submitted => "submitted",
+            AvailToEthStage::Finalized => "finalized",
+            AvailToEthStage::Committed => "committed",
+            AvailToEthStage::ProofFetched => "proof_fetched",
+            AvailToEthStage::Verified => "verified",
+            AvailToEthStage::Claimed => "claimed",
+        }
+    }
+}
+
+impl EthToAvailStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EthToAvailStage::Sent => "sent",
+            EthToAvailStage::Stored => "stored",
+            EthToAvailStage::ProofFetched => "proof_fetched",
+            EthToAvailStage::Executed => "executed",
+        }
+    }
+}
+
+/// Counts `error` if a bridge transaction reverted.
+pub fn record_revert(error: &BridgeError) {
+    match error {
+        BridgeError::Revert(e) => revert(e),
+        BridgeError::TransactionReverted(_) => transaction_reverted(),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "metrics")]
+mod imp {
+    use crate::avail_to_eth::AvailToEthStage;
+    use crate::eth_to_avail::EthToAvailStage;
+    use crate::AvailBridgeContract::AvailBridgeContractErrors;
+    use crate::{AvailBridgeContract, BridgeApiError, BridgeError, ProofSource, Result};
+    use alloy_sol_types::SolError;
+    use hyper::header::CONTENT_TYPE;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Method, Request, Response, Server, StatusCode};
+    use prometheus::{
+        Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+    };
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::OnceLock;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+    use tracing::warn;
+
+    pub const ENABLED: bool = true;
+
+    impl ProofSource {
+        fn as_str(&self) -> &'static str {
+            match self {
+                ProofSource::BridgeApi => "bridge_api",
+                ProofSource::Rpc => "rpc",
+            }
+        }
+    }
+
+    /// Signature of a bridge custom error, e.g. `AlreadyBridged()`. The match is exhaustive, so a
+    /// new error in the ABI does not go unlabeled.
+    fn revert_signature(error: &AvailBridgeContract::AvailBridgeContractErrors) -> &'static str {
+        macro_rules! signatures {
+            ($($name:ident),* $(,)?) => {
+                match error {
+                    $(AvailBridgeContractErrors::$name(_) => AvailBridgeContract::$name::SIGNATURE,)*
+                }
+            };
+        }
+        signatures!(
+            AccessControlBadConfirmation,
+            AccessControlEnforcedDefaultAdminDelay,
+            AccessControlEnforcedDefaultAdminRules,
+            AccessControlInvalidDefaultAdmin,
+            AccessControlUnauthorizedAccount,
+            AddressEmptyCode,
+            AddressInsufficientBalance,
+            AlreadyBridged,
+            ArrayLengthMismatch,
+            BlobRootEmpty,
+            BridgeRootEmpty,
+            DataRootCommitmentEmpty,
+            EnforcedPause,
+            ExpectedPause,
+            FailedInnerCall,
+            FeeTooLow,
+            InvalidAssetId,
+            InvalidDataLength,
+            InvalidDataRootProof,
+            InvalidDestinationOrAmount,
+            InvalidDomain,
+            InvalidFungibleTokenTransfer,
+            InvalidInitialization,
+            InvalidLeaf,
+            InvalidMerkleProof,
+            InvalidMessage,
+            NotInitializing,
+            ReentrancyGuardReentrantCall,
+            SafeCastOverflowedUintDowncast,
+            SafeERC20FailedOperation,
+            UnlockFailed,
+            WithdrawFailed,
+        )
+    }
+
+    /// Bridge API endpoint of a request path without its parameters, e.g. `/eth/proof`.
+    fn bridge_api_endpoint(path: &str) -> String {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .take(2)
+            .fold(String::new(), |endpoint, segment| endpoint + "/" + segment)
+    }
+
+    fn bridge_api_outcome(result: Result<(), &BridgeApiError>) -> &'static str {
+        match result {
+            Ok(()) => "ok",
+            Err(BridgeApiError::Request(_)) => "request_error",
+            Err(BridgeApiError::Status { .. }) => "status_error",
+            Err(BridgeApiError::Decode { .. }) => "decode_error",
+        }
+    }
+
+    struct Metrics {
+        registry: Registry,
+        transfers: IntCounterVec,
+        commitment_delay: Histogram,
+        proof_fetch: HistogramVec,
+        claim_gas: Histogram,
+        reverts: IntCounterVec,
+        bridge_api_requests: IntCounterVec,
+        relay_failures: IntCounterVec,
+    }
+
+    impl Metrics {
+        fn new() -> Self {
+            let registry = Registry::new();
+            let transfers = IntCounterVec::new(
+                Opts::new(
+                    "bridge_transfer_stages_total",
+                    "Transfers that reached a stage",
+                ),
+                &["direction", "stage"],
+            )
+            .expect("valid metric");
+            let commitment_delay = Histogram::with_opts(
+                HistogramOpts::new(
+                    "bridge_avail_commitment_delay_seconds",
+                    "Time from the Avail block of a transfer to the Ethereum block committing it",
+                )
+                .buckets(vec![
+                    60.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0, 14400.0,
+                ]),
+            )
+            .expect("valid metric");
+            let proof_fetch = HistogramVec::new(
+                HistogramOpts::new("bridge_proof_fetch_seconds", "Time to fetch a proof")
+                    .buckets(vec![0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0]),
+                &["direction", "source"],
+            )
+            .expect("valid metric");
+            let claim_gas = Histogram::with_opts(
+                HistogramOpts::new("bridge_claim_gas_used", "Gas used by Ethereum claims").buckets(
+                    vec![
+                        50_000.0,
+                        100_000.0,
+                        150_000.0,
+                        200_000.0,
+                        300_000.0,
+                        500_000.0,
+                        1_000_000.0,
+                    ],
+                ),
+            )
+            .expect("valid metric");
+            let reverts = IntCounterVec::new(
+                Opts::new("bridge_reverts_total", "Bridge contract reverts by error"),
+                &["error"],
+            )
+            .expect("valid metric");
+            let bridge_api_requests = IntCounterVec::new(
+                Opts::new(
+                    "bridge_api_requests_total",
+                    "Bridge API requests by outcome",
+                ),
+                &["endpoint", "outcome"],
+            )
+            .expect("valid metric");
+            let relay_failures = IntCounterVec::new(
+                Opts::new(
+                    "bridge_relay_failures_total",
+                    "Relayer failures that were logged and skipped, by reason",
+                ),
+                &["reason"],
+            )
+            .expect("valid metric");
+            for collector in [
+                Box::new(transfers.clone()) as Box<dyn prometheus::core::Collector>,
+                Box::new(commitment_delay.clone()),
+                Box::new(proof_fetch.clone()),
+                Box::new(claim_gas.clone()),
+                Box::new(reverts.clone()),
+                Box::new(bridge_api_requests.clone()),
+                Box::new(relay_failures.clone()),
+            ] {
+                registry.register(collector).expect("unique metric");
+            }
+            Metrics {
+                registry,
+                transfers,
+                commitment_delay,
+                proof_fetch,
+                claim_gas,
+                reverts,
+                bridge_api_requests,
+                relay_failures,
+            }
+        }
+    }
+
+    fn metrics() -> &'static Metrics {
+        static METRICS: OnceLock<Metrics> = OnceLock::new();
+        METRICS.get_or_init(Metrics::new)
+    }
+
+    pub fn avail_to_eth_stage(stage: AvailToEthStage) {
+        metrics()
+            .transfers
+            .with_label_values(&["avail_to_eth", stage.as_str()])
+            .inc();
+    }
+
+    pub fn eth_to_avail_stage(stage: EthToAvailStage) {
+        metrics()
+            .transfers
+            .with_label_values(&["eth_to_avail", stage.as_str()])
+            .inc();
+    }
+
+    pub fn commitment_delay(delay: Duration) {
+        metrics().commitment_delay.observe(delay.as_secs_f64());
+    }
+
+    pub fn avail_to_eth_proof_fetched(source: ProofSource, latency: Duration) {
+        metrics()
+            .proof_fetch
+            .with_label_values(&["avail_to_eth", source.as_str()])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn eth_to_avail_proof_fetched(source: ProofSource, latency: Duration) {
+        metrics()
+            .proof_fetch
+            .with_label_values(&["eth_to_avail", source.as_str()])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn claim_gas_used(gas_used: u128) {
+        metrics().claim_gas.observe(gas_used as f64);
+    }
+
+    pub fn revert(error: &AvailBridgeContract::AvailBridgeContractErrors) {
+        metrics()
+            .reverts
+            .with_label_values(&[revert_signature(error)])
+            .inc();
+    }
+
+    /// Counts a transaction that was mined but reverted, whose error is not known.
+    pub fn transaction_reverted() {
+        metrics()
+            .reverts
+            .with_label_values(&["transaction_reverted"])
+            .inc();
+    }
+
+    pub fn bridge_api_request(path: &str, result: Result<(), &BridgeApiError>) {
+        metrics()
+            .bridge_api_requests
+            .with_label_values(&[&bridge_api_endpoint(path), bridge_api_outcome(result)])
+            .inc();
+    }
+
+    /// Counts a relayer failure the daemon kept running after, e.g. `transfer` or `journal`.
+    pub fn relay_failure(reason: &str) {
+        metrics().relay_failures.with_label_values(&[reason]).inc();
+    }
+
+    /// Current metrics in the Prometheus text format.
+    pub fn render() -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&metrics().registry.gather(), &mut buffer)
+            .expect("metrics encode as text");
+        String::from_utf8(buffer).expect("metrics text is UTF-8")
+    }
+
+    /// Serves the metrics at `http://<address>/metrics` until the returned task is aborted.
+    pub async fn serve(address: SocketAddr) -> crate::Result<JoinHandle<()>> {
+        let server = Server::try_bind(&address)
+            .map_err(|e| BridgeError::Config(format!("Cannot serve metrics on {address}: {e}")))?
+            .serve(make_service_fn(|_| async {
+                Ok::<_, Infallible>(service_fn(|request| async move {
+                    Ok::<_, Infallible>(respond(&request))
+                }))
+            }));
+        Ok(tokio::spawn(async move {
+            if let Err(e) = server.await {
+                warn!(error = %e, "Metrics server stopped");
+            }
+        }))
+    }
+
+    /// Answers `GET /metrics` with the metrics and anything else with 404.
+    fn respond(request: &Request<Body>) -> Response<Body> {
+        let response = if request.method() == Method::GET && request.uri().path() == "/metrics" {
+            Response::builder()
+                .header(CONTENT_TYPE, prometheus::TEXT_FORMAT)
+                .body(Body::from(render()))
+        } else {
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::empty())
+        };
+        response.expect("valid response")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn request(method: Method, uri: &str) -> Request<Body> {
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        }
+
+        #[test]
+        fn only_get_metrics_is_served() {
+            transaction_reverted();
+            let response = respond(&request(Method::GET, "/metrics?name=reverts"));
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CONTENT_TYPE], prometheus::TEXT_FORMAT);
+            for (method, uri) in [
+                (Method::POST, "/metrics"),
+                (Method::GET, "/"),
+                (Method::GET, "/metricsfoo"),
+            ] {
+                let response = respond(&request(method, uri));
+                assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod imp {
+    use crate::avail_to_eth::AvailToEthStage;
+    use crate::eth_to_avail::EthToAvailStage;
+    use crate::{AvailBridgeContract, BridgeApiError, BridgeError, ProofSource, Result};
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    pub const ENABLED: bool = false;
+
+    pub fn avail_to_eth_stage(_stage: AvailToEthStage) {}
+
+    pub fn eth_to_avail_stage(_stage: EthToAvailStage) {}
+
+    pub fn commitment_delay(_delay: Duration) {}
+
+    pub fn avail_to_eth_proof_fetched(_source: ProofSource, _latency: Duration) {}
+
+    pub fn eth_to_avail_proof_fetched(_source: ProofSource, _latency: Duration) {}
+
+    pub fn claim_gas_used(_gas_used: u128) {}
+
+    pub fn revert(_error: &AvailBridgeContract::AvailBridgeContractErrors) {}
+
+    pub fn transaction_reverted() {}
+
+    pub fn bridge_api_request(_path: &str, _result: Result<(), &BridgeApiError>) {}
+
+    pub fn relay_failure(_reason: &str) {}
+
+    pub fn render() -> String {
+        String::new()
+    }
+
+    pub async fn serve(_address: SocketAddr) -> Result<JoinHandle<()>> {
+        Err(BridgeError::Config(
+            "Metrics require building with the `metrics` feature".into(),
+        ))
+    }
+}
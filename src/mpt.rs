@@ -0,0 +1,566 @@
+use crate::AccountStorageProof;
+use alloy::primitives::{keccak256, Address, B256, U256};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MptProofError {
+    /// A proof node is not valid RLP or not a valid trie node.
+    InvalidNode(&'static str),
+    /// A proof node does not hash to the reference in its parent (or the root).
+    HashMismatch { expected: B256, computed: B256 },
+    /// The proof ends before reaching the key.
+    IncompleteProof,
+    /// The proof shows that the account does not exist.
+    AccountNotFound(Address),
+    /// The storage slot does not hold the expected value.
+    UnexpectedValue {
+        slot: B256,
+        expected: B256,
+        value: B256,
+    },
+}
+
+impl fmt::Display for MptProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MptProofError::InvalidNode(e) => write!(f, "Invalid trie node: {e}"),
+            MptProofError::HashMismatch { expected, computed } => {
+                write!(
+                    f,
+                    "Trie node hash mismatch: expected {expected}, got {computed}"
+                )
+            }
+            MptProofError::IncompleteProof => write!(f, "Proof ends before reaching the key"),
+            MptProofError::AccountNotFound(address) => {
+                write!(f, "Account {address} does not exist")
+            }
+            MptProofError::UnexpectedValue {
+                slot,
+                expected,
+                value,
+            } => write!(f, "Slot {slot} holds {value}, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for MptProofError {}
+
+/// Account fields stored in the state trie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrieAccount {
+    pub nonce: U256,
+    pub balance: U256,
+    pub storage_root: B256,
+    pub code_hash: B256,
+}
+
+/// Verifies `proof` for `address` against the execution `state_root`.
+pub fn verify_account_proof(
+    state_root: B256,
+    address: Address,
+    proof: &[Vec<u8>],
+) -> Result<TrieAccount, MptProofError> {
+    let value = verify_proof(state_root, keccak256(address), proof)?
+        .ok_or(MptProofError::AccountNotFound(address))?;
+    let fields = decode_list(&value)?;
+    let [nonce, balance, storage_root, code_hash] = fields.as_slice() else {
+        return Err(MptProofError::InvalidNode("account is not a 4 item list"));
+    };
+    Ok(TrieAccount {
+        nonce: decode_uint(nonce.as_string()?)?,
+        balance: decode_uint(balance.as_string()?)?,
+        storage_root: decode_hash(storage_root.as_string()?)?,
+        code_hash: decode_hash(code_hash.as_string()?)?,
+    })
+}
+
+/// Verifies `proof` for `slot` against an account's `storage_root` and returns the slot's
+/// value, which is zero if the proof shows the slot is empty.
+pub fn verify_storage_proof(
+    storage_root: B256,
+    slot: B256,
+    proof: &[Vec<u8>],
+) -> Result<B256, MptProofError> {
+    let Some(value) = verify_proof(storage_root, keccak256(slot), proof)? else {
+        return Ok(B256::ZERO);
+    };
+    let value = decode_uint(decode_item(&value)?.0.as_string()?)?;
+    Ok(B256::from(value.to_be_bytes::<32>()))
+}
+
+impl AccountStorageProof {
+    /// Checks that `bridge` is in the state with `state_root` and that its storage `slot` holds
+    /// `expected`, the same way Avail does before executing a message.
+    pub fn verify(
+        &self,
+        state_root: B256,
+        bridge: Address,
+        slot: B256,
+        expected: B256,
+    ) -> Result<(), MptProofError> {
+        let account = verify_account_proof(state_root, bridge, &self.account_proof)?;
+        let value = verify_storage_proof(account.storage_root, slot, &self.storage_proof)?;
+        if value != expected {
+            return Err(MptProofError::UnexpectedValue {
+                slot,
+                expected,
+                value,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Walks `proof` from `root` along `path` and returns the RLP encoded value at the end, or `None`
+/// if the proof shows there is no value for the path.
+fn verify_proof(
+    root: B256,
+    path: B256,
+    proof: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, MptProofError> {
+    let nibbles = path
+        .iter()
+        .flat_map(|b| [b >> 4, b & 0x0f])
+        .collect::<Vec<_>>();
+    let mut path = nibbles.as_slice();
+    let mut proof = proof.iter();
+    let mut next = NodeRef::Hash(root);
+
+    loop {
+        let node = match next {
+            NodeRef::Hash(expected) => {
+                let node = proof.next().ok_or(MptProofError::IncompleteProof)?;
+                let computed = keccak256(node);
+                if computed != expected {
+                    return Err(MptProofError::HashMismatch { expected, computed });
+                }
+                node.clone()
+            }
+            NodeRef::Inline(node) => node,
+        };
+        let items = decode_list(&node)?;
+        match items.len() {
+            17 => {
+                let Some((&nibble, rest)) = path.split_first() else {
+                    let value = items[16].as_string()?;
+                    return Ok((!value.is_empty()).then(|| value.to_vec()));
+                };
+                let child = &items[nibble as usize];
+                if child.is_empty() {
+                    return Ok(None);
+                }
+                next = child.node_ref()?;
+                path = rest;
+            }
+            2 => {
+                let (is_leaf, key) = decode_hex_prefix(items[0].as_string()?)?;
+                if is_leaf {
+                    if path == key.as_slice() {
+                        return Ok(Some(items[1].as_string()?.to_vec()));
+                    }
+                    return Ok(None);
+                }
+                let Some(rest) = path.strip_prefix(key.as_slice()) else {
+                    return Ok(None);
+                };
+                next = items[1].node_ref()?;
+                path = rest;
+            }
+            _ => return Err(MptProofError::InvalidNode("unexpected number of items")),
+        }
+    }
+}
+
+enum NodeRef {
+    Hash(B256),
+    /// Nodes shorter than 32 bytes are embedded in their parent instead of hashed.
+    Inline(Vec<u8>),
+}
+
+enum Rlp<'a> {
+    String(&'a [u8]),
+    List { raw: &'a [u8] },
+}
+
+impl<'a> Rlp<'a> {
+    fn as_string(&self) -> Result<&'a [u8], MptProofError> {
+        match self {
+            Rlp::String(s) => Ok(s),
+            Rlp::List { .. } => Err(MptProofError::InvalidNode("expected a string, got a list")),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Rlp::String(s) if s.is_empty())
+    }
+
+    fn node_ref(&self) -> Result<NodeRef, MptProofError> {
+        match self {
+            Rlp::String(s) if s.len() == 32 => Ok(NodeRef::Hash(B256::from_slice(s))),
+            Rlp::String(_) => Err(MptProofError::InvalidNode("invalid child reference")),
+            Rlp::List { raw } => Ok(NodeRef::Inline(raw.to_vec())),
+        }
+    }
+}
+
+/// Decodes one RLP item from the front of `buf`, returning it and the remaining bytes.
+fn decode_item(buf: &[u8]) -> Result<(Rlp<'_>, &[u8]), MptProofError> {
+    let (&prefix, rest) = buf
+        .split_first()
+        .ok_or(MptProofError::InvalidNode("unexpected end of RLP"))?;
+    let (header_len, payload_len, is_list) = match prefix {
+        0x00..=0x7f => return Ok((Rlp::String(&buf[..1]), rest)),
+        0x80..=0xb7 => (1, (prefix - 0x80) as usize, false),
+        0xb8..=0xbf => {
+            let len_of_len = (prefix - 0xb7) as usize;
+            (1 + len_of_len, decode_length(rest, len_of_len)?, false)
+        }
+        0xc0..=0xf7 => (1, (prefix - 0xc0) as usize, true),
+        0xf8..=0xff => {
+            let len_of_len = (prefix - 0xf7) as usize;
+            (1 + len_of_len, decode_length(rest, len_of_len)?, true)
+        }
+    };
+    let end = header_len
+        .checked_add(payload_len)
+        .filter(|end| *end <= buf.len())
+        .ok_or(MptProofError::InvalidNode("RLP item longer than its input"))?;
+    let item = if is_list {
+        Rlp::List { raw: &buf[..end] }
+    } else {
+        Rlp::String(&buf[header_len..end])
+    };
+    Ok((item, &buf[end..]))
+}
+
+fn decode_length(buf: &[u8], len_of_len: usize) -> Result<usize, MptProofError> {
+    if len_of_len > buf.len() || len_of_len > std::mem::size_of::<usize>() {
+        return Err(MptProofError::InvalidNode("invalid RLP length"));
+    }
+    Ok(buf[..len_of_len]
+        .iter()
+        .fold(0usize, |len, b| (len << 8) | *b as usize))
+}
+
+/// Decodes `buf`, which must be exactly one RLP list, into its items.
+fn decode_list(buf: &[u8]) -> Result<Vec<Rlp<'_>>, MptProofError> {
+    let (item, rest) = decode_item(buf)?;
+    let Rlp::List { raw } = item else {
+        return Err(MptProofError::InvalidNode("expected a list"));
+    };
+    if !rest.is_empty() {
+        return Err(MptProofError::InvalidNode("trailing bytes after list"));
+    }
+    let header_len = match raw[0] {
+        0xc0..=0xf7 => 1,
+        prefix => 1 + (prefix - 0xf7) as usize,
+    };
+    let mut payload = &raw[header_len..];
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (item, rest) = decode_item(payload)?;
+        items.push(item);
+        payload = rest;
+    }
+    Ok(items)
+}
+
+/// Decodes a hex-prefix encoded path into its nibbles and whether it belongs to a leaf.
+fn decode_hex_prefix(encoded: &[u8]) -> Result<(bool, Vec<u8>), MptProofError> {
+    let (&first, rest) = encoded
+        .split_first()
+        .ok_or(MptProofError::InvalidNode("empty path"))?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(MptProofError::InvalidNode("invalid path flag"));
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(rest.iter().flat_map(|b| [b >> 4, b & 0x0f]));
+    Ok((flag & 2 == 2, nibbles))
+}
+
+fn decode_uint(bytes: &[u8]) -> Result<U256, MptProofError> {
+    if bytes.len() > 32 {
+        return Err(MptProofError::InvalidNode("integer longer than 32 bytes"));
+    }
+    Ok(U256::from_be_slice(bytes))
+}
+
+fn decode_hash(bytes: &[u8]) -> Result<B256, MptProofError> {
+    if bytes.len() != 32 {
+        return Err(MptProofError::InvalidNode("hash is not 32 bytes"));
+    }
+    Ok(B256::from_slice(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage_proof::{message_storage_slot, MESSAGE_MAPPING_SLOT};
+    use alloy::primitives::Bytes;
+    use hex_literal::hex;
+    use serde::Deserialize;
+
+    /// `eth_getProof` response for the bridge with the `isSent` slots of messages 7 (sent) and
+    /// 1000 (not sent), and the state root it was taken at.
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Fixture {
+        state_root: B256,
+        response: ProofResponse,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ProofResponse {
+        address: Address,
+        account_proof: Vec<Bytes>,
+        balance: U256,
+        nonce: U256,
+        code_hash: B256,
+        storage_hash: B256,
+        storage_proof: Vec<StorageProof>,
+    }
+
+    #[derive(Deserialize)]
+    struct StorageProof {
+        key: B256,
+        value: U256,
+        proof: Vec<Bytes>,
+    }
+
+    fn fixture() -> Fixture {
+        serde_json::from_str(include_str!("../tests/fixtures/eth_get_proof.json")).unwrap()
+    }
+
+    fn nodes(proof: &[Bytes]) -> Vec<Vec<u8>> {
+        proof.iter().map(|node| node.to_vec()).collect()
+    }
+
+    #[test]
+    fn account_proof_verifies() {
+        let Fixture {
+            state_root,
+            response,
+        } = fixture();
+        let account = verify_account_proof(
+            state_root,
+            response.address,
+            &nodes(&response.account_proof),
+        )
+        .unwrap();
+        assert_eq!(
+            account,
+            TrieAccount {
+                nonce: response.nonce,
+                balance: response.balance,
+                storage_root: response.storage_hash,
+                code_hash: response.code_hash,
+            }
+        );
+    }
+
+    #[test]
+    fn storage_proof_verifies() {
+        let response = fixture().response;
+        let sent = &response.storage_proof[0];
+        assert_eq!(sent.key, message_storage_slot(7, MESSAGE_MAPPING_SLOT));
+        let value =
+            verify_storage_proof(response.storage_hash, sent.key, &nodes(&sent.proof)).unwrap();
+        assert_eq!(value, B256::from(sent.value.to_be_bytes::<32>()));
+        assert_eq!(value, keccak256(b"message leaf 7"));
+    }
+
+    #[test]
+    fn account_storage_proof_verifies() {
+        let Fixture {
+            state_root,
+            response,
+        } = fixture();
+        let sent = &response.storage_proof[0];
+        let proof = AccountStorageProof {
+            account_proof: nodes(&response.account_proof),
+            storage_proof: nodes(&sent.proof),
+        };
+        proof
+            .verify(
+                state_root,
+                response.address,
+                sent.key,
+                keccak256(b"message leaf 7"),
+            )
+            .unwrap();
+        assert_eq!(
+            proof.verify(
+                state_root,
+                response.address,
+                sent.key,
+                keccak256(b"message leaf 8"),
+            ),
+            Err(MptProofError::UnexpectedValue {
+                slot: sent.key,
+                expected: keccak256(b"message leaf 8"),
+                value: keccak256(b"message leaf 7"),
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_node_is_rejected() {
+        let Fixture {
+            state_root,
+            response,
+        } = fixture();
+        let mut proof = nodes(&response.account_proof);
+        let last = proof.last_mut().unwrap();
+        let expected = keccak256(&last);
+        *last.last_mut().unwrap() ^= 1;
+        let computed = keccak256(&last);
+        assert_eq!(
+            verify_account_proof(state_root, response.address, &proof),
+            Err(MptProofError::HashMismatch { expected, computed })
+        );
+
+        let mut proof = nodes(&response.account_proof);
+        proof[0][5] ^= 1;
+        assert!(matches!(
+            verify_account_proof(state_root, response.address, &proof),
+            Err(MptProofError::HashMismatch { expected, .. }) if expected == state_root
+        ));
+    }
+
+    #[test]
+    fn truncated_proof_is_incomplete() {
+        let Fixture {
+            state_root,
+            response,
+        } = fixture();
+        let mut proof = nodes(&response.account_proof);
+        proof.pop();
+        assert_eq!(
+            verify_account_proof(state_root, response.address, &proof),
+            Err(MptProofError::IncompleteProof)
+        );
+
+        let sent = &response.storage_proof[0];
+        let mut proof = nodes(&sent.proof);
+        proof.pop();
+        assert_eq!(
+            verify_storage_proof(response.storage_hash, sent.key, &proof),
+            Err(MptProofError::IncompleteProof)
+        );
+    }
+
+    #[test]
+    fn empty_slot_is_proven_zero() {
+        let response = fixture().response;
+        let unsent = &response.storage_proof[1];
+        assert_eq!(unsent.key, message_storage_slot(1000, MESSAGE_MAPPING_SLOT));
+        assert_eq!(unsent.value, U256::ZERO);
+        assert_eq!(
+            verify_storage_proof(response.storage_hash, unsent.key, &nodes(&unsent.proof)),
+            Ok(B256::ZERO)
+        );
+    }
+
+    /// Trie of 0x00..01 => 0x2a, 0x00..02 => 0x2b and 0xff..ff => 0x2c. The first two share 63
+    /// nibbles, so the branch below their extension and both leaves are under 32 bytes and
+    /// embedded in the extension node.
+    const INLINE_ROOT: [u8; 32] =
+        hex!("0541f19a551ce89db9146870c4e0ea6c8bedbb49307d4135e437f69387140c77");
+
+    fn inline_proof() -> Vec<Vec<u8>> {
+        vec![
+            hex!("f851a0476b3009690a56a116922b2bb8787d7c5e6340ca555e5db01a74cb81e6250e698080808080808080808080808080a00f7abc0d415117ca8c4f408ed3e631eba221e052d1be3110d0e419520ce04d7c80").to_vec(),
+            hex!("f7a00000000000000000000000000000000000000000000000000000000000000000d580c2202ac2202b8080808080808080808080808080").to_vec(),
+        ]
+    }
+
+    fn key(last: u8) -> B256 {
+        let mut key = B256::ZERO;
+        key[31] = last;
+        key
+    }
+
+    #[test]
+    fn inline_nodes_are_followed() {
+        let root = B256::from(INLINE_ROOT);
+        assert_eq!(
+            verify_proof(root, key(1), &inline_proof()),
+            Ok(Some(vec![0x2a]))
+        );
+        assert_eq!(
+            verify_proof(root, key(2), &inline_proof()),
+            Ok(Some(vec![0x2b]))
+        );
+        assert_eq!(verify_proof(root, key(3), &inline_proof()), Ok(None));
+    }
+
+    #[test]
+    fn rlp_items_decode() {
+        assert!(matches!(
+            decode_item(&[0x2a]),
+            Ok((Rlp::String([0x2a]), []))
+        ));
+        assert!(matches!(decode_item(&[0x80]), Ok((Rlp::String([]), []))));
+        assert!(matches!(
+            decode_item(&[0x82, 0x04, 0x00, 0xff]),
+            Ok((Rlp::String([0x04, 0x00]), [0xff]))
+        ));
+        let long = [&[0xb8, 0x38][..], &[0x11; 56]].concat();
+        assert!(matches!(decode_item(&long), Ok((Rlp::String(s), [])) if s == [0x11; 56]));
+
+        // A branch node is longer than 55 bytes, so its list length takes an extra byte.
+        let proof = inline_proof();
+        let items = decode_list(&proof[0]).unwrap();
+        assert_eq!(items.len(), 17);
+        assert!(items[1].is_empty());
+        assert!(matches!(items[0].node_ref(), Ok(NodeRef::Hash(_))));
+        let items = decode_list(&proof[1]).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[1].node_ref(), Ok(NodeRef::Inline(_))));
+    }
+
+    #[test]
+    fn invalid_rlp_is_rejected() {
+        assert!(matches!(
+            decode_item(&[]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+        assert!(matches!(
+            decode_item(&[0x83, 0x01, 0x02]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+        assert!(matches!(
+            decode_item(&[0xb9, 0x01]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+        assert!(matches!(
+            decode_list(&[0x82, 0x01, 0x02]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+        assert!(matches!(
+            decode_list(&[0xc1, 0x01, 0x02]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+        assert!(matches!(
+            decode_list(&[0xc3, 0x82, 0x01]),
+            Err(MptProofError::InvalidNode(_))
+        ));
+    }
+
+    #[test]
+    fn hex_prefix_decodes() {
+        assert_eq!(decode_hex_prefix(&[0x00, 0x12]), Ok((false, vec![1, 2])));
+        assert_eq!(
+            decode_hex_prefix(&[0x1a, 0x12]),
+            Ok((false, vec![0xa, 1, 2]))
+        );
+        assert_eq!(decode_hex_prefix(&[0x20]), Ok((true, vec![])));
+        assert_eq!(decode_hex_prefix(&[0x3f]), Ok((true, vec![0xf])));
+        assert!(decode_hex_prefix(&[0x40]).is_err());
+    }
+}
@@ -0,0 +1,919 @@
+use crate::avail_to_eth::{AvailToEthKind, AvailToEthTransfer};
+use crate::eth_to_avail::{EthToAvailTransfer, NonceTracker};
+use crate::head_watcher::{Head, HeadNotifications, HeadWatcher, StoredEthHead};
+use crate::journal::{Journal, JournaledTransfer, ResumeReport};
+use crate::metrics;
+use crate::status::execution_status;
+use crate::vectorx::{get_logs_in_range, HeadTracker};
+use crate::{
+    connect_avail, AvailBridgeContract, BridgeError, Config, Result, DEFAULT_POLL_INTERVAL,
+    ETHEREUM_DOMAIN,
+};
+use alloy::primitives::Address;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::{BlockNumberOrTag, Filter, Log};
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::SolEvent;
+use alloy_transport::Transport;
+use avail_rust::avail::runtime_types::avail_core::data_proof::message::MessageType as RuntimeMessageType;
+use avail_rust::avail::vector::events::MessageSubmitted;
+use avail_rust::avail_core::data_proof::Message;
+use avail_rust::subxt::events::Phase;
+use avail_rust::{Keypair, SDK};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::sync::{watch, Semaphore, SemaphorePermit};
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::Instant;
+use tracing::{error, info, info_span, warn, Instrument};
+
+/// Journal cursor of the Avail -> Ethereum relayer, the last scanned Avail block.
+pub const AVAIL_TO_ETH_CURSOR: &str = "avail_to_eth_relayer";
+
+/// Journal cursor of the Ethereum -> Avail relayer, the last scanned Ethereum block.
+pub const ETH_TO_AVAIL_CURSOR: &str = "eth_to_avail_relayer";
+
+/// Default number of transfers proved and claimed or executed at the same time.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageType {
+    ArbitraryMessage,
+    FungibleToken,
+}
+
+impl From<&Message> for MessageType {
+    fn from(message: &Message) -> Self {
+        match message {
+            Message::ArbitraryMessage(_) => MessageType::ArbitraryMessage,
+            Message::FungibleToken { .. } => MessageType::FungibleToken,
+        }
+    }
+}
+
+impl From<&RuntimeMessageType> for MessageType {
+    fn from(message_type: &RuntimeMessageType) -> Self {
+        match message_type {
+            RuntimeMessageType::ArbitraryMessage => MessageType::ArbitraryMessage,
+            RuntimeMessageType::FungibleToken => MessageType::FungibleToken,
+        }
+    }
+}
+
+/// Selects the messages a relayer handles. Empty lists match everything.
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+    pub recipients: Vec<H256>,
+    pub message_types: Vec<MessageType>,
+}
+
+impl MessageFilter {
+    pub fn matches_recipient(&self, recipient: H256) -> bool {
+        self.recipients.is_empty() || self.recipients.contains(&recipient)
+    }
+
+    pub fn matches(&self, recipient: H256, message_type: MessageType) -> bool {
+        self.matches_recipient(recipient)
+            && (self.message_types.is_empty() || self.message_types.contains(&message_type))
+    }
+}
+
+/// State shared by a relayer and its transfer tasks.
+struct RelayContext {
+    journal: Mutex<Journal>,
+    /// Limits the transfers fetching proofs and claiming or executing at the same time.
+    /// Transfers waiting for the other chain's head do not hold a permit.
+    permits: Semaphore,
+}
+
+impl RelayContext {
+    fn journal(&self) -> MutexGuard<'_, Journal> {
+        self.journal.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handed to each transfer task by its relayer.
+struct TaskHandle {
+    context: Arc<RelayContext>,
+    /// Latest block of the source chain covered by the destination chain's head.
+    head: watch::Receiver<u64>,
+    stop: watch::Receiver<bool>,
+}
+
+impl TaskHandle {
+    fn journal(&self) -> MutexGuard<'_, Journal> {
+        self.context.journal()
+    }
+
+    /// Waits until the followed head reaches `block_number`. Returns `false` if the relayer
+    /// stops first.
+    async fn wait_for_head(&mut self, block_number: u64) -> Result<bool> {
+        loop {
+            if *self.head.borrow_and_update() >= block_number {
+                return Ok(true);
+            }
+            tokio::select! {
+                changed = self.head.changed() => changed.map_err(|_| {
+                    BridgeError::Transfer("Head follower stopped".into())
+                })?,
+                _ = self.stop.changed() => return Ok(false),
+            }
+        }
+    }
+
+    async fn permit(&self) -> Result<SemaphorePermit<'_>> {
+        self.context
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| BridgeError::Transfer(format!("Relayer stopped: {e}")))
+    }
+}
+
+/// Transfer tasks of a relayer and their results. Each task returns `false` if it stopped
+/// before completing the transfer.
+struct RelayTasks {
+    context: Arc<RelayContext>,
+    head: watch::Receiver<u64>,
+    stop: watch::Sender<bool>,
+    tasks: JoinSet<(u64, Result<bool>)>,
+    report: ResumeReport,
+}
+
+impl RelayTasks {
+    fn new(journal: Journal, concurrency: usize, head: watch::Receiver<u64>) -> Self {
+        RelayTasks {
+            context: Arc::new(RelayContext {
+                journal: Mutex::new(journal),
+                permits: Semaphore::new(concurrency),
+            }),
+            head,
+            stop: watch::channel(false).0,
+            tasks: JoinSet::new(),
+            report: ResumeReport::default(),
+        }
+    }
+
+    fn journal(&self) -> MutexGuard<'_, Journal> {
+        self.context.journal()
+    }
+
+    fn spawn<F, Fut>(&mut self, id: u64, task: F)
+    where
+        F: FnOnce(TaskHandle) -> Fut,
+        Fut: Future<Output = Result<bool>> + Send + 'static,
+    {
+        let task = task(TaskHandle {
+            context: self.context.clone(),
+            head: self.head.clone(),
+            stop: self.stop.subscribe(),
+        });
+        // A panic is caught here rather than surfacing as a `JoinError`, so it is recorded on the
+        // journal entry of its transfer like any other failure.
+        let task = async move {
+            let result = AssertUnwindSafe(task)
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(BridgeError::Transfer("Relay task panicked".to_string())));
+            (id, result)
+        };
+        self.tasks
+            .spawn(task.instrument(info_span!("relay_task", journal_id = id)));
+    }
+
+    /// Waits for the next task to finish and records its result. Returns `None` if no task is
+    /// running.
+    async fn join_next(&mut self) -> Option<()> {
+        let joined = self.tasks.join_next().await?;
+        self.record(joined);
+        Some(())
+    }
+
+    fn record(&mut self, joined: std::result::Result<(u64, Result<bool>), JoinError>) {
+        let (id, result) = match joined {
+            Ok(joined) => joined,
+            Err(e) => {
+                error!(error = %e, "Relay task failed");
+                metrics::relay_failure("task");
+                return;
+            }
+        };
+        match result {
+            Ok(true) => self.report.completed.push(id),
+            Ok(false) => {}
+            Err(e) => self.fail(id, &e),
+        }
+    }
+
+    /// Records a failed transfer. A journal that cannot be written is logged rather than
+    /// returned, so one failure does not stop the relayer.
+    fn fail(&mut self, id: u64, error: &BridgeError) {
+        warn!(journal_id = id, error = %error, "Transfer failed");
+        metrics::relay_failure("transfer");
+        if let Err(e) = self.journal().record_error(id, error) {
+            error!(journal_id = id, error = %e, "Failed to record the transfer error");
+            metrics::relay_failure("journal");
+        }
+        self.report.failed.push((id, format!("{error:#}")));
+    }
+
+    /// Stops tasks that are still waiting for the head and waits for the others to finish.
+    async fn finish(mut self) -> ResumeReport {
+        self.stop.send_replace(true);
+        while self.join_next().await.is_some() {}
+        self.report
+    }
+}
+
+/// First wait before scanning a block again after a failed scan.
+const MIN_SCAN_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Longest wait before scanning a block again, reached by doubling after each failure.
+const MAX_SCAN_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// Backoff of a relayer whose last scan failed. Transfers keep running while it waits.
+#[derive(Debug, Default)]
+struct ScanRetry {
+    delay: Option<Duration>,
+    until: Option<Instant>,
+}
+
+impl ScanRetry {
+    /// Starts waiting after a failed scan and returns how long.
+    fn failed(&mut self) -> Duration {
+        let delay = self.delay.map_or(MIN_SCAN_RETRY_DELAY, |delay| {
+            (delay * 2).min(MAX_SCAN_RETRY_DELAY)
+        });
+        self.delay = Some(delay);
+        self.until = Some(Instant::now() + delay);
+        delay
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn is_waiting(&self) -> bool {
+        self.until.is_some()
+    }
+
+    /// Stops waiting, keeping the delay so the next failure backs off further.
+    fn expire(&mut self) {
+        self.until = None;
+    }
+
+    /// Completes when the wait is over, never if not waiting.
+    async fn elapsed(&self) {
+        match self.until {
+            Some(until) => tokio::time::sleep_until(until).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+/// Long-running Avail -> Ethereum relayer. It follows finalized Avail blocks for
+/// `MessageSubmitted` events to Ethereum, waits until VectorX commits them and claims them with
+/// `receiveMessage`/`receiveAVAIL`/`receiveETH`/`receiveERC20`. Messages sent through wrapper
+/// calls such as `utility.batch` or `proxy.proxy` are picked up too.
+///
+/// Every transfer and the last scanned block are recorded in the journal, so a restarted relayer
+/// continues where it stopped. Transfers are keyed by their block, extrinsic index and message id,
+/// so each message of an extrinsic sending several is relayed. A block that cannot be scanned is
+/// retried with backoff.
+pub struct AvailToEthRelayer {
+    config: Config,
+    eth_signer: PrivateKeySigner,
+    journal: Journal,
+    filter: MessageFilter,
+    concurrency: usize,
+    start_block: Option<u64>,
+    poll_interval: Duration,
+}
+
+impl AvailToEthRelayer {
+    pub fn new(config: &Config, eth_signer: PrivateKeySigner, journal: Journal) -> Self {
+        Self {
+            config: config.clone(),
+            eth_signer,
+            journal,
+            filter: MessageFilter::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+            start_block: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: MessageFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Avail block to start scanning from when the journal has no cursor yet. Defaults to the
+    /// latest finalized block.
+    pub fn with_start_block(mut self, block_number: u64) -> Self {
+        self.start_block = Some(block_number);
+        self
+    }
+
+    /// Sets the longest wait between checks of the VectorX head.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Runs until `shutdown` completes. Pending transfers in the journal are picked up first.
+    /// On shutdown no new transfers are started, transfers still waiting for VectorX stay
+    /// pending in the journal and claims that are already running are awaited.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> Result<ResumeReport> {
+        let sdk = connect_avail(&self.config.avail_rpc_url).await?;
+        let next_block = self
+            .journal
+            .cursor(AVAIL_TO_ETH_CURSOR)
+            .map(|cursor| cursor + 1)
+            .or(self.start_block);
+        info!(?next_block, "Relaying messages from Avail to Ethereum");
+        let (head_sender, vectorx_head) = watch::channel(0);
+        let follower = tokio::spawn(follow_vectorx_head(
+            self.config.clone(),
+            self.poll_interval,
+            head_sender,
+        ));
+        let mut relay = AvailToEthRelay {
+            config: self.config,
+            sdk,
+            eth_signer: self.eth_signer,
+            filter: self.filter,
+            next_block,
+            tasks: RelayTasks::new(self.journal, self.concurrency, vectorx_head),
+        };
+
+        let result = relay.relay(shutdown).await;
+
+        let report = relay.tasks.finish().await;
+        follower.abort();
+        result.map(|()| report)
+    }
+}
+
+struct AvailToEthRelay {
+    config: Config,
+    /// Shared by the scans and every claim.
+    sdk: SDK,
+    eth_signer: PrivateKeySigner,
+    filter: MessageFilter,
+    /// Next Avail block to scan, `None` until the first finalized block is seen.
+    next_block: Option<u64>,
+    tasks: RelayTasks,
+}
+
+impl AvailToEthRelay {
+    async fn relay(&mut self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        self.resume_pending();
+        let sdk = self.sdk.clone();
+        let mut blocks = sdk.api.blocks().subscribe_finalized().await?;
+        let mut finalized = None;
+        let mut retry = ScanRetry::default();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                Some(()) = self.tasks.join_next() => {}
+                block = blocks.next() => {
+                    let block = block.ok_or(BridgeError::Avail(
+                        "Finalized block subscription ended".into(),
+                    ))??;
+                    finalized = Some(block.number() as u64);
+                }
+                _ = retry.elapsed() => retry.expire(),
+            }
+            let Some(finalized) = finalized.filter(|_| !retry.is_waiting()) else {
+                continue;
+            };
+            match self.scan_until(&sdk, finalized).await {
+                Ok(()) => retry.reset(),
+                Err(e) => {
+                    let delay = retry.failed();
+                    warn!(
+                        block_number = self.next_block.unwrap_or(finalized),
+                        error = %e,
+                        ?delay,
+                        "Cannot scan Avail block, retrying"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Scans the blocks up to `finalized`, advancing the cursor after each one so a failed
+    /// block is scanned again next time.
+    async fn scan_until(&mut self, sdk: &SDK, finalized: u64) -> Result<()> {
+        for block_number in self.next_block.unwrap_or(finalized)..=finalized {
+            self.scan_block(sdk, block_number).await?;
+            self.tasks
+                .journal()
+                .set_cursor(AVAIL_TO_ETH_CURSOR, block_number)?;
+            self.next_block = Some(block_number + 1);
+        }
+        Ok(())
+    }
+
+    fn resume_pending(&mut self) {
+        let pending = self
+            .tasks
+            .journal()
+            .pending()
+            .filter_map(|entry| match entry.transfer {
+                JournaledTransfer::AvailToEth {
+                    kind: AvailToEthKind::Message,
+                    on_chain: true,
+                    block_hash: Some(block_hash),
+                    extrinsic_index: Some(extrinsic_index),
+                    message_id,
+                    ..
+                } => Some((entry.id, block_hash, extrinsic_index, message_id)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (id, block_hash, extrinsic_index, message_id) in pending {
+            self.spawn(id, block_hash, extrinsic_index, message_id);
+        }
+    }
+
+    /// Records and starts relaying every matching `MessageSubmitted` to Ethereum in the block.
+    /// The event is only emitted by successful calls, wherever they are nested.
+    async fn scan_block(&mut self, sdk: &SDK, block_number: u64) -> Result<()> {
+        let block_hash = sdk
+            .rpc
+            .chain
+            .get_block_hash(Some(block_number as u32))
+            .await?;
+        let events = sdk.api.blocks().at(block_hash).await?.events().await?;
+        for event in events.iter() {
+            let event = event?;
+            let Some(submitted) = event.as_event::<MessageSubmitted>()? else {
+                continue;
+            };
+            let Phase::ApplyExtrinsic(extrinsic_index) = event.phase() else {
+                continue;
+            };
+            if submitted.destination_domain != ETHEREUM_DOMAIN
+                || !self
+                    .filter
+                    .matches(submitted.to, MessageType::from(&submitted.message_type))
+            {
+                continue;
+            }
+            let mut journal = self.tasks.journal();
+            if journal
+                .find_avail_to_eth_message(block_hash, extrinsic_index, submitted.message_id)
+                .is_some()
+            {
+                continue;
+            }
+            let id = journal.record_finalized_avail_to_eth(
+                AvailToEthKind::Message,
+                block_hash,
+                extrinsic_index,
+                Some(submitted.message_id),
+                true,
+            )?;
+            drop(journal);
+            info!(
+                journal_id = id,
+                ?block_hash,
+                extrinsic_index,
+                message_id = submitted.message_id,
+                "Found message to Ethereum"
+            );
+            self.spawn(id, block_hash, extrinsic_index, Some(submitted.message_id));
+        }
+        Ok(())
+    }
+
+    fn spawn(&mut self, id: u64, block_hash: H256, extrinsic_index: u32, message_id: Option<u64>) {
+        let config = self.config.clone();
+        let sdk = self.sdk.clone();
+        let eth_signer = self.eth_signer.clone();
+        self.tasks.spawn(id, move |handle| {
+            claim_transfer(
+                handle,
+                id,
+                config,
+                sdk,
+                eth_signer,
+                block_hash,
+                extrinsic_index,
+                message_id,
+            )
+        });
+    }
+}
+
+/// Claims one Avail -> Ethereum transfer. Returns `false` if the relayer stopped before the
+/// transfer was committed by VectorX.
+async fn claim_transfer(
+    mut handle: TaskHandle,
+    id: u64,
+    config: Config,
+    sdk: SDK,
+    eth_signer: PrivateKeySigner,
+    block_hash: H256,
+    extrinsic_index: u32,
+    message_id: Option<u64>,
+) -> Result<bool> {
+    let mut transfer = AvailToEthTransfer::resume(
+        &config,
+        sdk,
+        eth_signer,
+        AvailToEthKind::Message,
+        block_hash,
+        extrinsic_index,
+    )?;
+    if let Some(message_id) = message_id {
+        transfer = transfer.with_message_id(message_id);
+    }
+    let mut block_number = transfer.finalized().await?.block_number;
+    loop {
+        if !handle.wait_for_head(block_number).await? {
+            return Ok(false);
+        }
+        if transfer.try_committed().await?.is_some() {
+            break;
+        }
+        // The head moved past the block but its commitment is not visible yet.
+        block_number = *handle.head.borrow() + 1;
+    }
+    handle.journal().update_avail_to_eth(id, &transfer)?;
+
+    let _permit = handle.permit().await?;
+    transfer.proof().await?;
+    handle.journal().update_avail_to_eth(id, &transfer)?;
+    transfer.claim().await?;
+    handle.journal().update_avail_to_eth(id, &transfer)?;
+    Ok(true)
+}
+
+/// Long-running Ethereum -> Avail relayer. It tails `MessageSent` logs of the bridge up to the
+/// finalized Ethereum block, waits until the vector pallet on Avail stores an Ethereum head
+/// covering them and executes them on Avail from `avail_signer`. Messages that were already
+/// executed, e.g. by another relayer, are skipped.
+///
+/// Every transfer and the last scanned block are recorded in the journal, so a restarted relayer
+/// continues where it stopped. Logs that cannot be scanned are retried with backoff. Only
+/// messages sent by calling the bridge directly can be decoded and relayed.
+pub struct EthToAvailRelayer {
+    config: Config,
+    avail_signer: Keypair,
+    journal: Journal,
+    filter: MessageFilter,
+    concurrency: usize,
+    start_block: Option<u64>,
+    poll_interval: Duration,
+}
+
+impl EthToAvailRelayer {
+    pub fn new(config: &Config, avail_signer: Keypair, journal: Journal) -> Self {
+        Self {
+            config: config.clone(),
+            avail_signer,
+            journal,
+            filter: MessageFilter::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+            start_block: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: MessageFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Ethereum block to start scanning from when the journal has no cursor yet. Defaults to
+    /// the finalized block.
+    pub fn with_start_block(mut self, block_number: u64) -> Self {
+        self.start_block = Some(block_number);
+        self
+    }
+
+    /// Sets how often new logs are fetched and the longest wait between checks of the
+    /// Ethereum head stored on Avail.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Runs until `shutdown` completes. Pending transfers in the journal are picked up first.
+    /// On shutdown no new transfers are started, transfers still waiting for the Ethereum head
+    /// stay pending in the journal and executions that are already running are awaited.
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> Result<ResumeReport> {
+        let sdk = connect_avail(&self.config.avail_rpc_url).await?;
+        let next_block = self
+            .journal
+            .cursor(ETH_TO_AVAIL_CURSOR)
+            .map(|cursor| cursor + 1)
+            .or(self.start_block);
+        info!(?next_block, "Relaying messages from Ethereum to Avail");
+        let (head_sender, eth_head) = watch::channel(0);
+        let follower = tokio::spawn(follow_stored_eth_head(
+            self.config.clone(),
+            sdk.clone(),
+            self.poll_interval,
+            head_sender,
+        ));
+        let mut relay = EthToAvailRelay {
+            config: self.config,
+            sdk,
+            avail_signer: self.avail_signer,
+            nonces: Arc::new(NonceTracker::new()),
+            filter: self.filter,
+            poll_interval: self.poll_interval,
+            next_block,
+            tasks: RelayTasks::new(self.journal, self.concurrency, eth_head),
+        };
+
+        let result = relay.relay(shutdown).await;
+
+        let report = relay.tasks.finish().await;
+        follower.abort();
+        result.map(|()| report)
+    }
+}
+
+struct EthToAvailRelay {
+    config: Config,
+    sdk: SDK,
+    avail_signer: Keypair,
+    nonces: Arc<NonceTracker>,
+    filter: MessageFilter,
+    poll_interval: Duration,
+    /// Next Ethereum block to scan, `None` until the first finalized block is seen.
+    next_block: Option<u64>,
+    tasks: RelayTasks,
+}
+
+impl EthToAvailRelay {
+    async fn relay(&mut self, shutdown: impl Future<Output = ()>) -> Result<()> {
+        self.resume_pending().await?;
+        let sdk = self.sdk.clone();
+        let provider = ProviderBuilder::new().on_http(self.config.parse_ethereum_url()?);
+        let bridge = self.config.parse_contract_address()?;
+        let mut interval = tokio::time::interval(self.poll_interval);
+        let mut retry = ScanRetry::default();
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                Some(()) = self.tasks.join_next() => {}
+                _ = interval.tick() => {}
+                _ = retry.elapsed() => retry.expire(),
+            }
+            if retry.is_waiting() {
+                continue;
+            }
+            match self.scan_finalized(&sdk, &provider, bridge).await {
+                Ok(()) => retry.reset(),
+                Err(e) => {
+                    let delay = retry.failed();
+                    warn!(
+                        next_block = ?self.next_block,
+                        error = %e,
+                        ?delay,
+                        "Cannot scan Ethereum logs, retrying"
+                    );
+                }
+            }
+        }
+    }
+
+    async fn resume_pending(&mut self) -> Result<()> {
+        let pending = self
+            .tasks
+            .journal()
+            .pending()
+            .filter_map(|entry| match entry.transfer {
+                JournaledTransfer::EthToAvail { eth_tx_hash, .. } => Some((entry.id, eth_tx_hash)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (id, tx_hash) in pending {
+            match EthToAvailTransfer::resume(&self.config, tx_hash).await {
+                Ok(transfer) => self.spawn(id, transfer),
+                Err(e) => self.tasks.fail(id, &e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Scans the bridge's `MessageSent` logs up to the finalized Ethereum block, advancing the
+    /// cursor after each chunk so a failed scan resumes where it stopped.
+    async fn scan_finalized<T, P>(&mut self, sdk: &SDK, provider: &P, bridge: Address) -> Result<()>
+    where
+        T: Transport + Clone,
+        P: Provider<T>,
+    {
+        let Some(finalized) = provider
+            .get_block_by_number(BlockNumberOrTag::Finalized, false)
+            .await?
+            .and_then(|block| block.header.number)
+        else {
+            return Ok(());
+        };
+        let filter = Filter::new()
+            .address(bridge)
+            .event_signature(AvailBridgeContract::MessageSent::SIGNATURE_HASH);
+        let mut chunk_size = self.config.log_chunk_size.max(1);
+        let mut from_block = self.next_block.unwrap_or(finalized);
+        while from_block <= finalized {
+            let to_block = (from_block + chunk_size - 1).min(finalized);
+            let Some(logs) = get_logs_in_range(provider, &filter, from_block, to_block).await?
+            else {
+                chunk_size = (chunk_size / 2).max(1);
+                continue;
+            };
+            for log in logs {
+                self.handle_log(sdk, &log).await?;
+            }
+            self.tasks
+                .journal()
+                .set_cursor(ETH_TO_AVAIL_CURSOR, to_block)?;
+            from_block = to_block + 1;
+            self.next_block = Some(from_block);
+        }
+        Ok(())
+    }
+
+    /// Records and starts executing the message sent in `log` if it matches the filter and was
+    /// not executed yet.
+    async fn handle_log(&mut self, sdk: &SDK, log: &Log) -> Result<()> {
+        let event = AvailBridgeContract::MessageSent::decode_log_data(&log.inner.data, true)?;
+        let Ok(message_id) = u64::try_from(event.messageId) else {
+            warn!(message_id = %event.messageId, "Message id out of range, skipping");
+            return Ok(());
+        };
+        let Some(tx_hash) = log.transaction_hash else {
+            return Ok(());
+        };
+        if !self.filter.matches_recipient(H256(event.to.0))
+            || self.tasks.journal().find_eth_to_avail(message_id).is_some()
+        {
+            return Ok(());
+        }
+        let transfer = match EthToAvailTransfer::resume(&self.config, tx_hash).await {
+            Ok(transfer) => transfer,
+            // Messages sent through another contract cannot be decoded from the transaction.
+            Err(e @ (BridgeError::Transfer(_) | BridgeError::Decode(_))) => {
+                warn!(message_id, %tx_hash, error = %e, "Cannot decode sent message, skipping");
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        let message = &transfer.sent().message;
+        if !self
+            .filter
+            .matches(message.to, MessageType::from(&message.message))
+        {
+            return Ok(());
+        }
+        if execution_status(sdk, message).await?.is_executed() {
+            info!(message_id, "Message was already executed, skipping");
+            return Ok(());
+        }
+        let id = self.tasks.journal().record_eth_to_avail(&transfer)?;
+        info!(journal_id = id, message_id, %tx_hash, "Found message to Avail");
+        self.spawn(id, transfer);
+        Ok(())
+    }
+
+    fn spawn(&mut self, id: u64, transfer: EthToAvailTransfer) {
+        let transfer = transfer.with_sdk(self.sdk.clone());
+        let avail_signer = self.avail_signer.clone();
+        let nonces = self.nonces.clone();
+        self.tasks.spawn(id, move |handle| {
+            execute_transfer(handle, id, transfer, avail_signer, nonces)
+        });
+    }
+}
+
+/// Executes one Ethereum -> Avail transfer. Returns `false` if the relayer stopped before Avail
+/// stored a head covering the message.
+async fn execute_transfer(
+    mut handle: TaskHandle,
+    id: u64,
+    mut transfer: EthToAvailTransfer,
+    avail_signer: Keypair,
+    nonces: Arc<NonceTracker>,
+) -> Result<bool> {
+    let mut block_number = transfer.sent().block_number;
+    loop {
+        if !handle.wait_for_head(block_number).await? {
+            return Ok(false);
+        }
+        if transfer.try_stored().await?.is_some() {
+            break;
+        }
+        block_number = *handle.head.borrow() + 1;
+    }
+    handle.journal().update_eth_to_avail(id, &transfer)?;
+
+    let _permit = handle.permit().await?;
+    transfer.proof().await?;
+    handle.journal().update_eth_to_avail(id, &transfer)?;
+    if transfer.execution_status().await?.is_executed() {
+        info!(parent: transfer.span(), "Message was executed by another account");
+        handle.journal().mark_eth_to_avail_executed(id)?;
+        return Ok(true);
+    }
+    transfer.execute_with_nonces(&avail_signer, &nonces).await?;
+    handle.journal().update_eth_to_avail(id, &transfer)?;
+    Ok(true)
+}
+
+/// Publishes the latest Avail block committed by VectorX, retrying after `poll_interval` when
+/// Ethereum cannot be reached.
+async fn follow_vectorx_head(config: Config, poll_interval: Duration, head: watch::Sender<u64>) {
+    while let Err(e) = watch_vectorx_head(&config, poll_interval, &head).await {
+        warn!(error = %e, "Cannot follow the VectorX head, retrying");
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+async fn watch_vectorx_head(
+    config: &Config,
+    poll_interval: Duration,
+    head: &watch::Sender<u64>,
+) -> Result<()> {
+    let provider = ProviderBuilder::new().on_http(config.parse_ethereum_url()?);
+    let contract = AvailBridgeContract::new(config.parse_contract_address()?, &provider);
+    let head_tracker = HeadTracker::for_bridge(&contract)
+        .await?
+        .with_log_scan(config.log_scan());
+    let notifications = match &config.ethereum_ws_url {
+        Some(ws_url) => HeadNotifications::vectorx_head_updates(ws_url, head_tracker.address())
+            .await
+            .inspect_err(|e| warn!(error = %e, "VectorX head subscription failed, polling"))
+            .ok(),
+        None => None,
+    };
+    head.send_replace(head_tracker.latest_block().await?);
+    let mut watcher =
+        HeadWatcher::new(head_tracker, poll_interval).with_notifications(notifications);
+    loop {
+        let next_block = *head.borrow() + 1;
+        let range = watcher.wait_until_covers(next_block).await?;
+        head.send_replace(range.end as u64);
+    }
+}
+
+/// Publishes the Ethereum block of the latest head stored on Avail, retrying after
+/// `poll_interval` when it cannot be read.
+async fn follow_stored_eth_head(
+    config: Config,
+    sdk: SDK,
+    poll_interval: Duration,
+    head: watch::Sender<u64>,
+) {
+    while let Err(e) = watch_stored_eth_head(&config, &sdk, poll_interval, &head).await {
+        warn!(error = %e, "Cannot follow the Ethereum head stored on Avail, retrying");
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+async fn watch_stored_eth_head(
+    config: &Config,
+    sdk: &SDK,
+    poll_interval: Duration,
+    head: &watch::Sender<u64>,
+) -> Result<()> {
+    let stored_head = StoredEthHead::new(sdk.clone(), config.parse_ethereum_url()?)
+        .with_beacon_genesis_time(config.beacon_genesis_time);
+    if let Some(stored) = stored_head.covering(0).await? {
+        head.send_replace(stored.block_number);
+    }
+    let notifications = HeadNotifications::avail_head_updates(sdk)
+        .await
+        .inspect_err(|e| warn!(error = %e, "Avail head subscription failed, polling"))
+        .ok();
+    let mut watcher =
+        HeadWatcher::new(stored_head, poll_interval).with_notifications(notifications);
+    loop {
+        let next_block = *head.borrow() + 1;
+        let stored = watcher.wait_until_covers(next_block).await?;
+        head.send_replace(stored.block_number);
+    }
+}
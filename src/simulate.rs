@@ -0,0 +1,59 @@
+use crate::{BridgeError, Result};
+use alloy_contract::{CallBuilder, CallDecoder};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionReceipt;
+use alloy_transport::Transport;
+
+/// Headroom added to the gas estimate, in percent, so a claim does not run out of gas when state
+/// changes between the estimate and inclusion.
+pub const GAS_LIMIT_MARGIN_PERCENT: u128 = 20;
+
+/// Result of a pre-flighted contract call.
+#[derive(Debug, Clone)]
+pub enum Preflight {
+    /// The call was only simulated.
+    Simulated { gas_estimate: u128 },
+    /// The call was simulated successfully, then broadcast and mined.
+    Sent(TransactionReceipt),
+}
+
+/// Simulates `call` with `eth_call` and estimates its gas with the exact same calldata. A revert
+/// is returned as `BridgeError::Revert` when it matches one of the bridge's custom errors.
+pub async fn simulate<T, P, D>(call: &CallBuilder<T, P, D>) -> Result<u128>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+    D: CallDecoder,
+{
+    call.call().await?;
+    Ok(call.estimate_gas().await?)
+}
+
+/// Simulates `call` and broadcasts it only if the simulation succeeds, with a gas limit of the
+/// estimate plus `GAS_LIMIT_MARGIN_PERCENT`. With `dry_run` nothing is broadcast.
+pub async fn send_simulated<T, P, D>(call: CallBuilder<T, P, D>, dry_run: bool) -> Result<Preflight>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+    D: CallDecoder,
+{
+    let gas_estimate = simulate(&call).await?;
+    if dry_run {
+        return Ok(Preflight::Simulated { gas_estimate });
+    }
+    let receipt = call
+        .gas(gas_limit(gas_estimate))
+        .send()
+        .await?
+        .get_receipt()
+        .await
+        .map_err(BridgeError::ethereum)?;
+    if !receipt.status() {
+        return Err(BridgeError::TransactionReverted(receipt.transaction_hash));
+    }
+    Ok(Preflight::Sent(receipt))
+}
+
+fn gas_limit(gas_estimate: u128) -> u128 {
+    gas_estimate + gas_estimate * GAS_LIMIT_MARGIN_PERCENT / 100
+}
@@ -0,0 +1,104 @@
+use crate::merkle::message_leaf;
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::Result;
+use alloy::primitives::U256;
+use alloy_provider::Provider;
+use alloy_transport::Transport;
+use avail_rust::avail::runtime_types::pallet_vector::pallet::MessageStatusEnum;
+use avail_rust::avail_core::data_proof::AddressedMessage as CoreAddressedMessage;
+use avail_rust::{avail, SDK};
+use sp_core::H256;
+
+/// Status of an Avail -> Ethereum message on the bridge contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Claimable,
+    AlreadyBridged,
+}
+
+/// Status of an Ethereum -> Avail message on the bridge contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SentStatus {
+    NotSent,
+    Sent,
+    /// The contract stores a different commitment for this message id.
+    Mismatch {
+        commitment: H256,
+    },
+}
+
+/// Status of an Ethereum -> Avail message in the vector pallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    NotExecuted,
+    Succeeded,
+    Failed,
+}
+
+impl ExecutionStatus {
+    /// Whether `execute` was already called for the message, successfully or not.
+    pub fn is_executed(&self) -> bool {
+        *self != ExecutionStatus::NotExecuted
+    }
+}
+
+/// Checks `isBridged` for the leaf of an Avail -> Ethereum message.
+pub async fn claim_status<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    message: &CoreAddressedMessage,
+) -> Result<ClaimStatus>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let leaf = message_leaf(message);
+    let bridged = bridge.isBridged(leaf.0.into()).call().await?._0;
+    Ok(if bridged {
+        ClaimStatus::AlreadyBridged
+    } else {
+        ClaimStatus::Claimable
+    })
+}
+
+/// Checks `isSent` for an Ethereum -> Avail message, comparing the stored commitment with the
+/// leaf of `message`.
+pub async fn sent_status<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    message: &CoreAddressedMessage,
+) -> Result<SentStatus>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let commitment = H256(bridge.isSent(U256::from(message.id)).call().await?._0.0);
+    Ok(if commitment.is_zero() {
+        SentStatus::NotSent
+    } else if commitment == message_leaf(message) {
+        SentStatus::Sent
+    } else {
+        SentStatus::Mismatch { commitment }
+    })
+}
+
+/// Reads the vector pallet's `MessageStatus` for an Ethereum -> Avail message.
+pub async fn execution_status(
+    sdk: &SDK,
+    message: &CoreAddressedMessage,
+) -> Result<ExecutionStatus> {
+    let status = sdk
+        .api
+        .storage()
+        .at_latest()
+        .await?
+        .fetch(
+            &avail::storage()
+                .vector()
+                .message_status(message_leaf(message)),
+        )
+        .await?;
+    Ok(match status {
+        Some(MessageStatusEnum::ExecutionSucceeded) => ExecutionStatus::Succeeded,
+        Some(MessageStatusEnum::ExecutionFailed) => ExecutionStatus::Failed,
+        Some(MessageStatusEnum::NotExecuted) | None => ExecutionStatus::NotExecuted,
+    })
+}
@@ -0,0 +1,90 @@
+use crate::{AccountStorageProof, BridgeError, Result};
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types_eth::BlockId;
+use alloy_sol_types::SolValue;
+use reqwest::Url;
+use sp_core::H256;
+
+/// Storage slot of the bridge's `isSent` mapping (message id => message leaf), which Avail
+/// reads when executing an Ethereum -> Avail message.
+pub const MESSAGE_MAPPING_SLOT: u64 = 1;
+
+/// Storage slot of `isSent[message_id]`, i.e. `keccak256(abi.encode(message_id, mapping_slot))`.
+pub fn message_storage_slot(message_id: u64, mapping_slot: u64) -> B256 {
+    keccak256((U256::from(message_id), U256::from(mapping_slot)).abi_encode())
+}
+
+/// Builds the account and storage proofs for `message_id` with `eth_getProof` against the
+/// bridge at the Ethereum block `block_hash`, without going through the Bridge API.
+pub async fn fetch_message_proof(
+    ethereum_url: Url,
+    bridge: Address,
+    block_hash: H256,
+    message_id: u64,
+    mapping_slot: u64,
+) -> Result<AccountStorageProof> {
+    let provider = ProviderBuilder::new().on_http(ethereum_url);
+    let slot = message_storage_slot(message_id, mapping_slot);
+    let response = provider
+        .get_proof(bridge, vec![slot])
+        .block_id(BlockId::hash(block_hash.0.into()))
+        .await?;
+    let storage_proof = response
+        .storage_proof
+        .into_iter()
+        .next()
+        .ok_or(BridgeError::Decode(
+            "eth_getProof returned no storage proof".into(),
+        ))?;
+    if storage_proof.value.is_zero() {
+        return Err(BridgeError::Transfer(format!(
+            "Message {message_id} is not sent at block {block_hash:?}"
+        )));
+    }
+    Ok(AccountStorageProof {
+        account_proof: response
+            .account_proof
+            .into_iter()
+            .map(|node| node.to_vec())
+            .collect(),
+        storage_proof: storage_proof
+            .proof
+            .into_iter()
+            .map(|node| node.to_vec())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn message_storage_slot_matches_solidity_layout() {
+        // keccak256(abi.encode(uint256(0), uint256(0)))
+        assert_eq!(
+            message_storage_slot(0, 0),
+            B256::from(hex!(
+                "ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5"
+            ))
+        );
+    }
+
+    #[test]
+    fn message_storage_slot_of_is_sent() {
+        assert_eq!(
+            message_storage_slot(7, MESSAGE_MAPPING_SLOT),
+            B256::from(hex!(
+                "dc686ec4a0ff239c70e7c7c36e8f853eced3bc8618f48d2b816da2a74311237e"
+            ))
+        );
+        assert_eq!(
+            message_storage_slot(u64::MAX, MESSAGE_MAPPING_SLOT),
+            B256::from(hex!(
+                "f96a472bdfe5b2a759c12ef9eaac602e223d6505b9b6a2b2dcce10ef8c8be639"
+            ))
+        );
+    }
+}
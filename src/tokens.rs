@@ -0,0 +1,148 @@
+use crate::avail_to_eth::{bridge_avail_to_eth, AvailToEthTransfer};
+use crate::eth_to_avail::{bridge_eth_to_avail, EthToAvailTransfer};
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::{BridgeError, Config, Result};
+use alloy::primitives::{Address, B256, U256};
+use alloy_provider::Provider;
+use alloy_signer_local::PrivateKeySigner;
+use alloy_sol_types::sol;
+use alloy_transport::Transport;
+use avail_rust::avail::vector::calls::types::send_message::Message as AvailMessage;
+use avail_rust::avail_core::data_proof::Message;
+use avail_rust::Keypair;
+use hex_literal::hex;
+use sp_core::H256;
+
+/// Asset id of AVAIL, bridged with `sendAVAIL`/`receiveAVAIL`.
+pub const AVAIL_ASSET_ID: H256 = H256::zero();
+
+/// Asset id of native ETH (`"ETH"` right padded), bridged with `sendETH`/`receiveETH`.
+pub const ETH_ASSET_ID: H256 = H256(hex!(
+    "4554480000000000000000000000000000000000000000000000000000000000"
+));
+
+sol!(
+    #[sol(rpc)]
+    interface IERC20 {
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+    }
+);
+
+/// Resolves the ERC20 token registered for `asset_id` in the bridge's `tokens` mapping.
+pub async fn token_address<T, P>(
+    bridge: &AvailBridgeContractInstance<T, P>,
+    asset_id: H256,
+) -> Result<Address>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let token = bridge.tokens(asset_id.0.into()).call().await?._0;
+    if token == Address::ZERO {
+        return Err(BridgeError::Transfer(format!(
+            "No token registered for asset id {asset_id:?}"
+        )));
+    }
+    Ok(token)
+}
+
+/// Approves `spender` to transfer `amount` of `token` from `owner` unless the current allowance
+/// already covers it. Returns the approval transaction hash if one was sent.
+pub async fn ensure_allowance<T, P>(
+    provider: P,
+    token: Address,
+    owner: Address,
+    spender: Address,
+    amount: U256,
+) -> Result<Option<B256>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let erc20 = IERC20::new(token, provider);
+    let allowance = erc20.allowance(owner, spender).call().await?._0;
+    if allowance >= amount {
+        return Ok(None);
+    }
+    let receipt = erc20
+        .approve(spender, amount)
+        .from(owner)
+        .send()
+        .await?
+        .get_receipt()
+        .await
+        .map_err(BridgeError::ethereum)?;
+    if !receipt.status() {
+        return Err(BridgeError::TransactionReverted(receipt.transaction_hash));
+    }
+    Ok(Some(receipt.transaction_hash))
+}
+
+/// Sends `amount` of the ERC20 token registered for `asset_id` to `recipient` on Avail,
+/// approving the bridge first if needed.
+pub async fn bridge_erc20_to_avail(
+    config: &Config,
+    eth_signer: PrivateKeySigner,
+    asset_id: H256,
+    amount: u128,
+    recipient: H256,
+) -> Result<EthToAvailTransfer> {
+    if asset_id == AVAIL_ASSET_ID || asset_id == ETH_ASSET_ID {
+        return Err(BridgeError::Transfer(format!(
+            "Asset id {asset_id:?} is not an ERC20 asset"
+        )));
+    }
+    let message = Message::FungibleToken { asset_id, amount };
+    bridge_eth_to_avail(config, eth_signer, message, recipient).await
+}
+
+/// Sends `amount` of `asset_id` from Avail to `recipient` on Ethereum, claimed with
+/// `receiveERC20`.
+pub async fn bridge_erc20_to_eth(
+    config: &Config,
+    avail_signer: &Keypair,
+    eth_signer: PrivateKeySigner,
+    asset_id: H256,
+    amount: u128,
+    recipient: H256,
+) -> Result<AvailToEthTransfer> {
+    if asset_id == AVAIL_ASSET_ID || asset_id == ETH_ASSET_ID {
+        return Err(BridgeError::Transfer(format!(
+            "Asset id {asset_id:?} is not an ERC20 asset"
+        )));
+    }
+    let message = AvailMessage::FungibleToken { asset_id, amount };
+    bridge_avail_to_eth(config, avail_signer, eth_signer, message, recipient).await
+}
+
+/// Sends `amount` wei of native ETH to `recipient` on Avail with `sendETH`.
+pub async fn bridge_ether_to_avail(
+    config: &Config,
+    eth_signer: PrivateKeySigner,
+    amount: u128,
+    recipient: H256,
+) -> Result<EthToAvailTransfer> {
+    let message = Message::FungibleToken {
+        asset_id: ETH_ASSET_ID,
+        amount,
+    };
+    bridge_eth_to_avail(config, eth_signer, message, recipient).await
+}
+
+/// Sends `amount` wei of bridged ETH from Avail to `recipient` on Ethereum, claimed with
+/// `receiveETH`.
+pub async fn bridge_ether_to_eth(
+    config: &Config,
+    avail_signer: &Keypair,
+    eth_signer: PrivateKeySigner,
+    amount: u128,
+    recipient: H256,
+) -> Result<AvailToEthTransfer> {
+    let message = AvailMessage::FungibleToken {
+        asset_id: ETH_ASSET_ID,
+        amount,
+    };
+    bridge_avail_to_eth(config, avail_signer, eth_signer, message, recipient).await
+}
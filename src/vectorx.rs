@@ -0,0 +1,297 @@
+use crate::vectorx::VectorX::VectorXInstance;
+use crate::AvailBridgeContract::AvailBridgeContractInstance;
+use crate::{AvailHeadData, BridgeError, Result};
+use alloy::primitives::{keccak256, Address};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{Filter, Log};
+use alloy_sol_types::{sol, SolEvent, SolValue};
+use alloy_transport::{Transport, TransportError};
+use sp_core::H256;
+use tracing::warn;
+
+sol!(
+    #[sol(rpc, all_derives)]
+    interface VectorX {
+        event HeaderRangeCommitmentStored(
+            uint32 startBlock,
+            uint32 endBlock,
+            bytes32 dataCommitment,
+            bytes32 stateCommitment,
+            uint32 headerRangeCommitmentTreeSize
+        );
+        event HeadUpdate(uint32 blockNumber, bytes32 headerHash);
+
+        function latestBlock() external view returns (uint32);
+        function blockHeightToHeaderHash(uint32 blockNumber) external view returns (bytes32);
+        function dataRootCommitments(bytes32 rangeHash) external view returns (bytes32);
+        function stateRootCommitments(bytes32 rangeHash) external view returns (bytes32);
+    }
+);
+
+/// Default number of Ethereum blocks queried per `eth_getLogs` request.
+pub const DEFAULT_LOG_CHUNK_SIZE: u64 = 10_000;
+
+/// Default of how far back from the latest Ethereum block commitments are searched.
+pub const DEFAULT_MAX_LOG_LOOKBACK: u64 = 500_000;
+
+/// Limits of `eth_getLogs` scans. A chunk the provider rejects as too large is halved until it
+/// is accepted, so `chunk_size` only needs to fit the usual provider limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogScan {
+    /// Number of Ethereum blocks queried per request.
+    pub chunk_size: u64,
+    /// How far back from the latest Ethereum block commitments are searched.
+    pub max_lookback: u64,
+}
+
+impl Default for LogScan {
+    fn default() -> Self {
+        LogScan {
+            chunk_size: DEFAULT_LOG_CHUNK_SIZE,
+            max_lookback: DEFAULT_MAX_LOG_LOOKBACK,
+        }
+    }
+}
+
+/// Fetches the logs matching `filter` in `from_block..=to_block`. Returns `None` if the provider
+/// rejects the range as too large and it can still be split, so the caller retries with a
+/// smaller chunk.
+pub async fn get_logs_in_range<T, P>(
+    provider: &P,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<Vec<Log>>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let filter = filter.clone().from_block(from_block).to_block(to_block);
+    match provider.get_logs(&filter).await {
+        Ok(logs) => Ok(Some(logs)),
+        Err(e) if from_block < to_block && is_range_limit_error(&e) => {
+            warn!(from_block, to_block, error = %e, "Log range too large, halving it");
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Whether the provider rejected an `eth_getLogs` request for covering too many blocks or
+/// returning too many logs. Providers word this differently, so the message is matched loosely.
+fn is_range_limit_error(error: &TransportError) -> bool {
+    let Some(payload) = error.as_error_resp() else {
+        return false;
+    };
+    let message = payload.message.to_lowercase();
+    // -32005 is the "limit exceeded" code of EIP-1474, used by Infura and others.
+    payload.code == -32005
+        || [
+            "block range",
+            "range too large",
+            "range is too large",
+            "too many",
+            "more than",
+            "response size",
+            "limit exceeded",
+        ]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// A header range committed by VectorX. The data root commitment covers the data roots of the
+/// Avail blocks `start + 1..=end`, padded with zeros to `tree_size` leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommittedRange {
+    pub start: u32,
+    pub end: u32,
+    pub data_commitment: H256,
+    pub tree_size: u32,
+    /// Ethereum block containing the `HeaderRangeCommitmentStored` log.
+    pub ethereum_block: u64,
+}
+
+impl CommittedRange {
+    pub fn contains(&self, block_number: u64) -> bool {
+        block_number > self.start as u64 && block_number <= self.end as u64
+    }
+
+    /// Key of the range in `dataRootCommitments`, `keccak256(abi.encode(start, end))`.
+    pub fn range_hash(&self) -> H256 {
+        range_hash(self.start, self.end)
+    }
+
+    /// Position of the data root of `block_number` in the commitment tree.
+    pub fn data_root_index(&self, block_number: u64) -> Option<u32> {
+        self.contains(block_number)
+            .then(|| (block_number - self.start as u64 - 1) as u32)
+    }
+}
+
+impl From<CommittedRange> for AvailHeadData {
+    fn from(range: CommittedRange) -> Self {
+        AvailHeadData {
+            start: range.start as u64 + 1,
+            end: range.end as u64,
+        }
+    }
+}
+
+pub fn range_hash(start: u32, end: u32) -> H256 {
+    H256(keccak256((start, end).abi_encode()).0)
+}
+
+/// Finds the VectorX commitment covering the Avail block `block_number` by scanning
+/// `HeaderRangeCommitmentStored` events backwards from the latest Ethereum block. Returns `None`
+/// if the block is not committed yet.
+pub async fn find_committed_range<T, P>(
+    provider: &P,
+    vectorx: Address,
+    block_number: u64,
+    log_scan: LogScan,
+) -> Result<Option<CommittedRange>>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    let latest = provider.get_block_number().await?;
+    let oldest = latest.saturating_sub(log_scan.max_lookback);
+    let filter = Filter::new()
+        .address(vectorx)
+        .event_signature(VectorX::HeaderRangeCommitmentStored::SIGNATURE_HASH);
+    let mut chunk_size = log_scan.chunk_size.max(1);
+    let mut to_block = latest;
+    loop {
+        let from_block = to_block.saturating_sub(chunk_size - 1).max(oldest);
+        let Some(logs) = get_logs_in_range(provider, &filter, from_block, to_block).await? else {
+            chunk_size = (chunk_size / 2).max(1);
+            continue;
+        };
+        for log in logs.iter().rev() {
+            let event =
+                VectorX::HeaderRangeCommitmentStored::decode_log_data(&log.inner.data, true)?;
+            let range = CommittedRange {
+                start: event.startBlock,
+                end: event.endBlock,
+                data_commitment: H256(event.dataCommitment.0),
+                tree_size: event.headerRangeCommitmentTreeSize,
+                ethereum_block: log.block_number.ok_or(BridgeError::Decode(
+                    "Commitment log has no block number".into(),
+                ))?,
+            };
+            if range.contains(block_number) {
+                return Ok(Some(range));
+            }
+            if (range.end as u64) < block_number {
+                // Ranges are committed in order, so older events cannot contain the block.
+                return Ok(None);
+            }
+        }
+        if from_block == oldest {
+            return Err(BridgeError::Transfer(format!(
+                "No VectorX commitment for block {block_number} in the last {} Ethereum blocks",
+                log_scan.max_lookback
+            )));
+        }
+        to_block = from_block - 1;
+    }
+}
+
+/// Reads the Avail head committed by VectorX directly from Ethereum. It answers the same
+/// questions as the Bridge API's `/avl/head`, without trusting the API.
+pub struct HeadTracker<T, P> {
+    vectorx: VectorXInstance<T, P>,
+    log_scan: LogScan,
+}
+
+impl<T, P> HeadTracker<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T>,
+{
+    pub fn new(vectorx: Address, provider: P) -> Self {
+        HeadTracker {
+            vectorx: VectorX::new(vectorx, provider),
+            log_scan: LogScan::default(),
+        }
+    }
+
+    /// Sets the limits of the commitment log searches.
+    pub fn with_log_scan(mut self, log_scan: LogScan) -> Self {
+        self.log_scan = log_scan;
+        self
+    }
+
+    /// Creates a tracker for the VectorX contract used by `bridge`.
+    pub async fn for_bridge(bridge: &AvailBridgeContractInstance<T, P>) -> Result<Self>
+    where
+        P: Clone,
+    {
+        let vectorx = bridge.vectorx().call().await?._0;
+        Ok(HeadTracker::new(vectorx, bridge.provider().clone()))
+    }
+
+    pub fn address(&self) -> Address {
+        *self.vectorx.address()
+    }
+
+    /// Latest Avail block committed by VectorX.
+    pub async fn latest_block(&self) -> Result<u64> {
+        Ok(self.vectorx.latestBlock().call().await?._0 as u64)
+    }
+
+    /// Range of the latest VectorX commitment, the on-chain equivalent of `/avl/head`.
+    pub async fn head(&self) -> Result<CommittedRange> {
+        let latest_block = self.latest_block().await?;
+        find_committed_range(
+            self.vectorx.provider(),
+            self.address(),
+            latest_block,
+            self.log_scan,
+        )
+        .await?
+        .ok_or(BridgeError::Transfer(format!(
+            "No VectorX commitment found for its latest block {latest_block}"
+        )))
+    }
+
+    /// Returns the committed range containing `block_number`, or `None` if VectorX has not
+    /// reached the block yet. Only searches the logs once the block is committed.
+    pub async fn committed_range(&self, block_number: u64) -> Result<Option<CommittedRange>> {
+        if self.latest_block().await? < block_number {
+            return Ok(None);
+        }
+        find_committed_range(
+            self.vectorx.provider(),
+            self.address(),
+            block_number,
+            self.log_scan,
+        )
+        .await
+    }
+
+    /// Unix timestamp, in seconds, of the Ethereum block that committed `range`.
+    pub async fn commitment_timestamp(&self, range: &CommittedRange) -> Result<u64> {
+        let block = self
+            .vectorx
+            .provider()
+            .get_block_by_number(range.ethereum_block.into(), false)
+            .await?
+            .ok_or(BridgeError::Ethereum(format!(
+                "Block {} not found",
+                range.ethereum_block
+            )))?;
+        Ok(block.header.timestamp)
+    }
+
+    /// Data root commitment VectorX stores for `range`.
+    pub async fn data_root_commitment(&self, range: &CommittedRange) -> Result<H256> {
+        let commitment = self
+            .vectorx
+            .dataRootCommitments(range.range_hash().0.into())
+            .call()
+            .await?
+            ._0;
+        Ok(H256(commitment.0))
+    }
+}